@@ -0,0 +1,11 @@
+CREATE TABLE
+  upload_session (
+    id UUID PRIMARY KEY,
+    album_id UUID NOT NULL REFERENCES album (id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL,
+    upload_length BIGINT NOT NULL,
+    upload_offset BIGINT NOT NULL DEFAULT 0,
+    is_primary_album BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
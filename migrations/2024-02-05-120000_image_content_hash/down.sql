@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE upload_session
+DROP COLUMN IF EXISTS link_duplicates;
+
+DROP INDEX IF EXISTS image_content_hash_idx;
+
+ALTER TABLE image
+DROP COLUMN IF EXISTS content_hash;
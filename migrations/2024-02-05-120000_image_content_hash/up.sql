@@ -0,0 +1,7 @@
+ALTER TABLE image
+ADD COLUMN content_hash TEXT;
+
+CREATE UNIQUE INDEX image_content_hash_idx ON image (content_hash);
+
+ALTER TABLE upload_session
+ADD COLUMN link_duplicates BOOLEAN NOT NULL DEFAULT FALSE;
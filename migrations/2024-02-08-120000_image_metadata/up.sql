@@ -0,0 +1,18 @@
+CREATE TABLE
+  image_metadata (
+    image_id UUID PRIMARY KEY REFERENCES image (id) ON DELETE CASCADE,
+    taken_at TIMESTAMP,
+    camera_make TEXT,
+    camera_model TEXT,
+    lens_model TEXT,
+    exposure_time TEXT,
+    f_number DOUBLE PRECISION,
+    iso INT,
+    focal_length DOUBLE PRECISION,
+    orientation INT,
+    gps_latitude DOUBLE PRECISION,
+    gps_longitude DOUBLE PRECISION,
+    gps_altitude DOUBLE PRECISION,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
@@ -0,0 +1,28 @@
+CREATE TABLE
+  import_job (
+    id UUID PRIMARY KEY,
+    album_id UUID NOT NULL REFERENCES album (id) ON DELETE CASCADE,
+    album_path TEXT NOT NULL,
+    is_primary_album BOOLEAN NOT NULL DEFAULT TRUE,
+    link_duplicates BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL DEFAULT 'queued',
+    total_items INT NOT NULL DEFAULT 0,
+    processed_items INT NOT NULL DEFAULT 0,
+    failed_items INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
+
+CREATE TABLE
+  import_job_item (
+    id UUID PRIMARY KEY,
+    job_id UUID NOT NULL REFERENCES import_job (id) ON DELETE CASCADE,
+    file_name TEXT NOT NULL,
+    order_index INT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    error TEXT,
+    image_id UUID REFERENCES image (id) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
+
+CREATE INDEX import_job_item_job_id_idx ON import_job_item (job_id);
@@ -0,0 +1 @@
+ALTER TABLE import_job ADD COLUMN order_offset INT NOT NULL DEFAULT 0;
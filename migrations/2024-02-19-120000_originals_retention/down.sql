@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE upload_session
+DROP COLUMN IF EXISTS retention;
+
+ALTER TABLE import_job
+DROP COLUMN IF EXISTS retention;
+
+ALTER TABLE image
+DROP COLUMN IF EXISTS archive_path;
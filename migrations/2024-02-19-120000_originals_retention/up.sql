@@ -0,0 +1,8 @@
+ALTER TABLE image
+ADD COLUMN archive_path TEXT;
+
+ALTER TABLE import_job
+ADD COLUMN retention TEXT NOT NULL DEFAULT 'delete';
+
+ALTER TABLE upload_session
+ADD COLUMN retention TEXT NOT NULL DEFAULT 'delete';
@@ -0,0 +1,10 @@
+CREATE TABLE
+  lust_cleanup (
+    id UUID PRIMARY KEY,
+    bucket TEXT NOT NULL,
+    lust_image_id TEXT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
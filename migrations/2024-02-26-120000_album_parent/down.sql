@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS album_parent_id_idx;
+
+ALTER TABLE album
+DROP COLUMN IF EXISTS parent_id;
@@ -0,0 +1,4 @@
+ALTER TABLE album
+ADD COLUMN parent_id UUID REFERENCES album (id) ON DELETE CASCADE;
+
+CREATE INDEX album_parent_id_idx ON album (parent_id);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE image
+DROP COLUMN IF EXISTS paired_image_id;
+
+ALTER TABLE image
+DROP COLUMN IF EXISTS media_type;
@@ -0,0 +1,5 @@
+ALTER TABLE image
+ADD COLUMN media_type TEXT NOT NULL DEFAULT 'image';
+
+ALTER TABLE image
+ADD COLUMN paired_image_id UUID REFERENCES image (id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE image
+ADD COLUMN raw_format TEXT;
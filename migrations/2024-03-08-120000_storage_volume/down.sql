@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE import_job
+DROP COLUMN IF EXISTS volume_id;
+ALTER TABLE image
+DROP COLUMN IF EXISTS volume_id;
+
+DROP TABLE IF EXISTS storage_volume;
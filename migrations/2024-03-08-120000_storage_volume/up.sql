@@ -0,0 +1,16 @@
+CREATE TABLE
+  storage_volume (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    mount_path TEXT NOT NULL UNIQUE,
+    capacity_bytes BIGINT,
+    is_read_only BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
+
+ALTER TABLE image
+ADD COLUMN volume_id UUID REFERENCES storage_volume (id) ON DELETE SET NULL;
+
+ALTER TABLE import_job
+ADD COLUMN volume_id UUID REFERENCES storage_volume (id) ON DELETE SET NULL;
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS image_integrity_status_idx;
+
+ALTER TABLE image
+DROP COLUMN IF EXISTS integrity_checked_at,
+DROP COLUMN IF EXISTS integrity_status,
+DROP COLUMN IF EXISTS stored_hash,
+DROP COLUMN IF EXISTS lust_checksum;
@@ -0,0 +1,7 @@
+ALTER TABLE image
+ADD COLUMN lust_checksum BIGINT,
+ADD COLUMN stored_hash TEXT,
+ADD COLUMN integrity_status TEXT NOT NULL DEFAULT 'unverified',
+ADD COLUMN integrity_checked_at TIMESTAMP;
+
+CREATE INDEX image_integrity_status_idx ON image (integrity_status);
@@ -0,0 +1,27 @@
+CREATE TABLE
+  storage_migration (
+    id UUID PRIMARY KEY,
+    target_backend TEXT NOT NULL,
+    target_bucket TEXT NOT NULL,
+    delete_source BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL DEFAULT 'queued',
+    total_items INT NOT NULL DEFAULT 0,
+    processed_items INT NOT NULL DEFAULT 0,
+    failed_items INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
+
+CREATE TABLE
+  storage_migration_item (
+    id UUID PRIMARY KEY,
+    migration_id UUID NOT NULL REFERENCES storage_migration (id) ON DELETE CASCADE,
+    image_id UUID NOT NULL REFERENCES image (id) ON DELETE CASCADE,
+    source_path TEXT NOT NULL,
+    target_path TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    error TEXT,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
+
+CREATE INDEX storage_migration_item_migration_id_idx ON storage_migration_item (migration_id);
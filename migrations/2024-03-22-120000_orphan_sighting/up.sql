@@ -0,0 +1,5 @@
+CREATE TABLE
+  orphan_sighting (
+    path TEXT PRIMARY KEY,
+    first_seen_at TIMESTAMP NOT NULL DEFAULT NOW ()
+  );
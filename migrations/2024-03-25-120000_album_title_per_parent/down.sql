@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS album_root_title_idx;
+
+DROP INDEX IF EXISTS album_parent_id_title_idx;
+
+ALTER TABLE album
+ADD CONSTRAINT album_title_key UNIQUE (title);
@@ -0,0 +1,9 @@
+-- titles only have to be unique next to each other, e.g. `2023/01` and `2024/01`
+ALTER TABLE album
+DROP CONSTRAINT IF EXISTS album_title_key;
+
+CREATE UNIQUE INDEX album_parent_id_title_idx ON album (parent_id, title);
+
+CREATE UNIQUE INDEX album_root_title_idx ON album (title)
+WHERE
+  parent_id IS NULL;
@@ -1,27 +1,72 @@
-use axum::{extract::DefaultBodyLimit, middleware, response::IntoResponse, routing::get, Router};
-use reqwest::Client;
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::StatusCode,
+    middleware,
+    response::IntoResponse,
+    routing::{get, head, post, put},
+    Json, Router,
+};
+use serde_json::json;
+use crate::{
+    api::auth_middleware::mw_ctx_require,
+    config::config,
+    db::ModelManager,
+    services::{lust_client::CircuitState, store::Store},
+};
 
-use crate::{api::auth_middleware::mw_ctx_require, db::ModelManager};
-
-use super::image_handler::{get_image, get_raw_image};
+use super::archive_handler::upload_archive;
+use super::avatar_handler::{get_avatar, put_avatar};
+use super::image_handler::{get_image, get_media, get_original_image, get_raw_image};
+use super::resumable_handler::{create_upload, delete_upload, get_upload_offset, patch_upload};
+use super::upload_handler::upload_album_images;
 
 #[derive(Clone)]
 pub struct ApiState {
-    pub reqwest_client: Client,
+    pub store: Store,
     pub mm: ModelManager,
 }
 
-pub fn routes(mm: ModelManager) -> Router {
-    let reqwest_client = Client::new();
+pub fn routes(mm: ModelManager, store: Store) -> Router {
     Router::new()
         .route("/test", get(test))
         .route("/image/:image_id", get(get_image))
         .route("/raw_image/:album_id/:image_id", get(get_raw_image))
+        .route("/original/:image_id", get(get_original_image))
+        .route("/media/:image_id", get(get_media))
+        .route("/album/:album_id/images", post(upload_album_images))
+        .route("/archives", post(upload_archive))
+        .route("/avatar", put(put_avatar))
+        .route("/avatar/:user_id", get(get_avatar))
+        .route("/uploads", post(create_upload))
+        .route(
+            "/uploads/:upload_id",
+            head(get_upload_offset)
+                .patch(patch_upload)
+                .delete(delete_upload),
+        )
         .layer(middleware::from_fn_with_state(mm.clone(), mw_ctx_require))
-        .layer(DefaultBodyLimit::max(1024 * 1024 * 10))
-        .with_state(ApiState { reqwest_client, mm })
+        // added after the auth layer so probes don't need a session
+        .route("/health", get(health))
+        .layer(DefaultBodyLimit::max(config().MAX_UPLOAD_SIZE))
+        .with_state(ApiState { store, mm })
 }
 
 async fn test() -> impl IntoResponse {
     "User logged in".to_string()
 }
+
+/// Answers 503 while the Lust circuit breaker is open, so probes can tell Lust is down.
+async fn health(State(state): State<ApiState>) -> impl IntoResponse {
+    let lust = state.store.lust_health();
+    let status = match lust.as_ref().map(|health| health.state) {
+        Some(CircuitState::Open) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    (
+        status,
+        Json(json!({
+            "storage_backend": state.store.backend().as_str(),
+            "lust": lust,
+        })),
+    )
+}
@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    Json,
+};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use super::{api_handler::ApiState, error::Error, upload_handler::UploadResult};
+
+use crate::{
+    config::config,
+    domain::{
+        album::{AlbumDao, DbAlbum, DbCreateAlbum},
+        image::ImageDao,
+    },
+    ingest::{
+        archive::{extract_archive, ArchiveEntry},
+        find_sidecar, ingest_image, is_sidecar, scan_file,
+        staging::{
+            create_staging_file, open_staging_file, remove_staging_dir, remove_staging_file,
+            staging_path,
+        },
+        DuplicatePolicy, IngestOptions, NewImage, RetentionPolicy,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct ArchiveParams {
+    /// Album to unpack into, a new album named after the archive is created when missing.
+    album_id: Option<Uuid>,
+    /// File name of the archive.
+    name: Option<String>,
+    is_primary_album: Option<bool>,
+    on_duplicate: Option<DuplicatePolicy>,
+    retention: Option<RetentionPolicy>,
+}
+
+#[derive(Serialize)]
+pub struct ArchiveUploadResult {
+    album_id: Uuid,
+    files: Vec<UploadResult>,
+}
+
+/// Accepts a zip, tar or tar.gz body and ingests its files into an album in archive order.
+pub async fn upload_archive(
+    State(context): State<ApiState>,
+    Query(params): Query<ArchiveParams>,
+    body: Body,
+) -> Result<Json<ArchiveUploadResult>, Error> {
+    let staging_name = format!("archive-{}", Uuid::new_v4());
+    let extract_name = format!("{}.d", staging_name);
+
+    let res = unpack_archive(&context, &params, body, &staging_name, &extract_name).await;
+
+    remove_staging_file(&staging_name).await;
+    if tokio::fs::metadata(staging_path(&extract_name))
+        .await
+        .is_ok()
+    {
+        remove_staging_dir(&extract_name).await;
+    }
+
+    res.map(Json)
+}
+
+async fn unpack_archive(
+    context: &ApiState,
+    params: &ArchiveParams,
+    body: Body,
+    staging_name: &str,
+    extract_name: &str,
+) -> Result<ArchiveUploadResult, Error> {
+    // Validate the target before taking in the whole body.
+    let album_title = match params.album_id {
+        Some(_) => None,
+        None => {
+            let title = params
+                .name
+                .as_deref()
+                .map(archive_title)
+                .filter(|title| !title.is_empty())
+                .ok_or_else(|| Error::BadRequest("album_id or name is required".to_string()))?;
+            if AlbumDao::get_by_title(&context.mm, &title)?.is_some() {
+                return Err(Error::EntityExists);
+            }
+            Some(title)
+        }
+    };
+
+    create_staging_file(staging_name).await?;
+    let mut file = open_staging_file(staging_name).await?;
+    let mut written = 0;
+    let mut body = body.into_data_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| Error::BadRequest(e.to_string()))?;
+        written += chunk.len();
+        if written > config().MAX_UPLOAD_SIZE {
+            return Err(Error::PayloadTooLarge);
+        }
+        file.write_all(&chunk)
+            .await
+            .map_err(|_| Error::FailedToWriteFile)?;
+    }
+    file.flush().await.map_err(|_| Error::FailedToWriteFile)?;
+
+    let archive = PathBuf::from(staging_path(staging_name));
+    let dest = PathBuf::from(staging_path(extract_name));
+    let entries = tokio::task::spawn_blocking(move || extract_archive(&archive, &dest))
+        .await
+        .map_err(|e| Error::ServiceError(e.to_string()))??;
+    debug!("{:<12} - extracted {} entries", "ARCHIVE", entries.len());
+    // sidecars are picked up next to the files they describe
+    let entries = entries
+        .into_iter()
+        .filter(|entry| !is_sidecar(&entry.name))
+        .collect::<Vec<ArchiveEntry>>();
+
+    let album = match (params.album_id, album_title) {
+        (Some(album_id), _) => AlbumDao::get_by_id(&context.mm, &album_id)?,
+        (None, Some(title)) => create_album(context, title)?,
+        (None, None) => unreachable!("album title is checked above"),
+    };
+    let options = IngestOptions {
+        album_id: album.id,
+        is_primary_album: params.is_primary_album.unwrap_or(true),
+        on_duplicate: params.on_duplicate.unwrap_or_default(),
+        retention: params.retention.unwrap_or(config().ORIGINALS_RETENTION),
+    };
+    // Indexes are taken up front so the album keeps the archive order.
+    let order_offset = ImageDao::get_by_album_id(&context.mm, &album.id)?.len() as i32;
+
+    let mut files = stream::iter(entries.into_iter().enumerate())
+        .map(|(i, entry)| {
+            let album = &album;
+            let options = &options;
+            async move {
+                let order_index = Some(order_offset + i as i32);
+                (
+                    i,
+                    ingest_entry(context, album, options, entry, order_index).await,
+                )
+            }
+        })
+        .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    files.sort_by_key(|(i, _)| *i);
+
+    Ok(ArchiveUploadResult {
+        album_id: album.id,
+        files: files.into_iter().map(|(_, file)| file).collect(),
+    })
+}
+
+async fn ingest_entry(
+    context: &ApiState,
+    album: &DbAlbum,
+    options: &IngestOptions,
+    entry: ArchiveEntry,
+    order_index: Option<i32>,
+) -> UploadResult {
+    let title = entry
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| entry.name.clone());
+
+    let sidecar = match entry.path.parent() {
+        Some(dir) => find_sidecar(dir, &title).await,
+        None => None,
+    };
+
+    let res = match scan_file(&entry.path).await {
+        Ok(file) => {
+            ingest_image(
+                &context.mm,
+                context.store.as_ref(),
+                options,
+                NewImage {
+                    title,
+                    original_full_title: format!("{}/{}", album.original_title, entry.name),
+                    file,
+                    order_index,
+                    source: None,
+                    sidecar,
+                },
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    match res {
+        Ok(ingested) => UploadResult {
+            file: entry.name,
+            image_id: Some(ingested.image.id),
+            linked: ingested.linked,
+            error: None,
+        },
+        Err(e) => {
+            error!("{:<12} - failed to upload {}: {}", "ARCHIVE", entry.name, e);
+            UploadResult {
+                file: entry.name,
+                image_id: None,
+                linked: false,
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+fn create_album(context: &ApiState, title: String) -> Result<DbAlbum, Error> {
+    AlbumDao::create(
+        &context.mm,
+        DbCreateAlbum {
+            id: Uuid::new_v4(),
+            title: title.clone(),
+            description: None,
+            original_title: title,
+            parent_id: None,
+        },
+    )
+    .map_err(Into::into)
+}
+
+/// `holiday-2023.tar.gz` becomes `holiday-2023`.
+fn archive_title(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let lower = name.to_lowercase();
+    let stem_len = [".tar.gz", ".tgz", ".tar", ".zip"]
+        .iter()
+        .find(|extension| lower.ends_with(*extension))
+        .map(|extension| name.len() - extension.len())
+        .unwrap_or(name.len());
+    name[..stem_len].trim().to_string()
+}
@@ -0,0 +1,102 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::{api_handler::ApiState, error::Error};
+
+use crate::{
+    config::config,
+    domain::user::{avatar::replace_avatar, UserBmc},
+    ingest::{
+        scan::Spool,
+        staging::{create_staging_file, open_staging_file, remove_staging_file, staging_path},
+        ScannedFile,
+    },
+    services::store::GetOptions,
+    web::ctx::Ctx,
+};
+
+#[derive(Deserialize)]
+pub struct Avatar {
+    /// `small` or `medium`, the presets of the profile bucket.
+    size: Option<String>,
+}
+
+/// Takes the image as the raw body and replaces the avatar of the logged in user.
+pub async fn put_avatar(
+    State(context): State<ApiState>,
+    ctx: Ctx,
+    body: Body,
+) -> Result<Json<Value>, Error> {
+    let name = format!("avatar-{}", Uuid::new_v4());
+    let res = match spool_body(&name, body).await {
+        Ok(file) => replace_avatar(&context.mm, context.store.as_ref(), &ctx.user_id, &file)
+            .await
+            .map_err(Into::into),
+        Err(e) => Err(e),
+    };
+    remove_staging_file(&name).await;
+
+    let user = res?;
+    Ok(Json(json!({
+        "success": true,
+        "avatar_path": user.avatar_path,
+    })))
+}
+
+pub async fn get_avatar(
+    State(context): State<ApiState>,
+    Path(user_id): Path<Uuid>,
+    Query(payload): Query<Avatar>,
+) -> Result<impl IntoResponse, Error> {
+    let user = UserBmc::get_by_id(&context.mm, &user_id)?;
+    let avatar_path = user.avatar_path.ok_or(Error::AvatarNotFound)?;
+    let options = GetOptions {
+        size: Some(payload.size.unwrap_or_else(|| "small".to_string())),
+        format: None,
+    };
+
+    let stored = context
+        .store
+        .get(&config().LUST_PROFILE_BUCKET, &avatar_path, options)
+        .await
+        .map_err(|e| {
+            if e.is_not_found() {
+                Error::AvatarNotFound
+            } else {
+                Error::ServiceError(e.to_string())
+            }
+        })?;
+
+    let mut file = stored.body.into_response();
+    let new_headers: &mut HeaderMap = file.headers_mut();
+    stored.headers.into_iter().for_each(|(k, v)| {
+        if let Some(header_name) = k {
+            new_headers.insert(header_name, v);
+        }
+    });
+
+    Ok(file)
+}
+
+async fn spool_body(name: &str, body: Body) -> Result<ScannedFile, Error> {
+    create_staging_file(name).await?;
+    let mut spool = Spool::new(open_staging_file(name).await?, staging_path(name));
+    let mut body = body.into_data_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| Error::BadRequest(e.to_string()))?;
+        if spool.size() + chunk.len() as u64 > config().MAX_UPLOAD_SIZE as u64 {
+            return Err(Error::PayloadTooLarge);
+        }
+        spool.write(&chunk).await?;
+    }
+    Ok(spool.finish().await?)
+}
@@ -4,6 +4,7 @@ use derive_more::Display;
 use tracing::debug;
 
 use crate::db::Error as DbError;
+use crate::ingest::Error as IngestError;
 use crate::web::crypt::Error as CryptError;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -22,6 +23,30 @@ pub enum Error {
     #[display(fmt = "Failed to read file")]
     FailedToReadFile,
 
+    #[display(fmt = "Failed to write file")]
+    FailedToWriteFile,
+
+    #[display(fmt = "Bad request: {}", _0)]
+    BadRequest(String),
+
+    #[display(fmt = "Bad image")]
+    BadImage,
+
+    #[display(fmt = "Entity exists")]
+    EntityExists,
+
+    #[display(fmt = "Original not found")]
+    OriginalNotFound,
+
+    #[display(fmt = "Media not found")]
+    MediaNotFound,
+
+    #[display(fmt = "Avatar not found")]
+    AvatarNotFound,
+
+    #[display(fmt = "Payload too large")]
+    PayloadTooLarge,
+
     #[display(fmt = "Internal server error: {}", _0)]
     ServiceError(String),
 }
@@ -38,15 +63,41 @@ impl From<CryptError> for Error {
     }
 }
 
+impl From<IngestError> for Error {
+    fn from(e: IngestError) -> Self {
+        match e {
+            IngestError::BadImage => Error::BadImage,
+            IngestError::BadArchive => Error::BadRequest("Unsupported or corrupt archive".to_string()),
+            IngestError::ArchiveTooLarge => Error::PayloadTooLarge,
+            IngestError::EntityExists => Error::EntityExists,
+            IngestError::FailedToReadFile => Error::FailedToReadFile,
+            IngestError::FailedToWriteFile => Error::FailedToWriteFile,
+            IngestError::DbError(e) => Error::DbError(e),
+            IngestError::ServiceError(e) => Error::ServiceError(e.to_string()),
+        }
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         debug!("{:<12} - model::Error {self:?}", "INTO_RES");
         let mut response = match self {
+            Error::DbError(DbError::DbEntityNotFound)
+            | Error::OriginalNotFound
+            | Error::MediaNotFound
+            | Error::AvatarNotFound => StatusCode::NOT_FOUND.into_response(),
             Error::DbError(ref e) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
             }
 
-            Error::FailedToReadFile => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Error::FailedToReadFile | Error::FailedToWriteFile => {
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+
+            Error::BadRequest(ref e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+            Error::BadImage => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+            Error::EntityExists => StatusCode::CONFLICT.into_response(),
+            Error::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE.into_response(),
 
             Error::LoginFailPwdNotMatching | Error::AuthorizationError(_) => {
                 StatusCode::UNAUTHORIZED.into_response()
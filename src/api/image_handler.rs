@@ -1,18 +1,33 @@
 use axum::{
-    extract::{Path, Query, State},
+    body::Body,
+    extract::{Path, Query, Request, State},
     http::{header, HeaderMap},
     response::IntoResponse,
 };
+use imagesize::{image_type, ImageType};
 use serde::Deserialize;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 use tracing::error;
+use uuid::Uuid;
 
 use super::{api_handler::ApiState, error::Error};
 
-use crate::{config::config, services::lust::Lust, utils::read_file};
+use crate::{
+    config::config,
+    domain::{
+        image::{ImageDao, MEDIA_VIDEO},
+        storage_volume::StorageVolumeDao,
+    },
+    ingest::{media::media_path, raw},
+    services::store::GetOptions,
+    utils::read_file,
+};
 
 #[derive(Deserialize)]
 pub struct Image {
     size: Option<String>,
+    format: Option<String>,
 }
 
 pub async fn get_image(
@@ -20,22 +35,20 @@ pub async fn get_image(
     Path(image_id): Path<String>,
     Query(payload): Query<Image>,
 ) -> Result<impl IntoResponse, Error> {
-    let client = context.reqwest_client;
-    let mut params = vec![];
-
-    match payload.size {
-        Some(size) => params.push(("size".to_string(), size)),
-        None => (),
-    }
+    let options = GetOptions {
+        size: payload.size,
+        format: payload.format,
+    };
 
-    let (response, headers) =
-        Lust::get_file(&client, &config().LUST_BUCKET, &image_id, Some(params))
-            .await
-            .map_err(|e| Error::ServiceError(e.to_string()))?;
+    let stored = context
+        .store
+        .get(&config().LUST_BUCKET, &image_id, options)
+        .await
+        .map_err(|e| Error::ServiceError(e.to_string()))?;
 
-    let mut file = response.into_response();
+    let mut file = stored.body.into_response();
     let new_headers: &mut HeaderMap = file.headers_mut();
-    headers.into_iter().for_each(|(k, v)| {
+    stored.headers.into_iter().for_each(|(k, v)| {
         if let Some(header_name) = k {
             new_headers.insert(header_name, v);
         }
@@ -44,25 +57,97 @@ pub async fn get_image(
     Ok(file)
 }
 
+#[derive(Deserialize)]
+pub struct RawImage {
+    volume_id: Option<Uuid>,
+}
+
 pub async fn get_raw_image(
+    State(context): State<ApiState>,
     Path((album_id, image_id)): Path<(String, String)>,
-) -> impl IntoResponse {
+    Query(payload): Query<RawImage>,
+) -> Result<impl IntoResponse, Error> {
+    let volume = StorageVolumeDao::resolve(&context.mm, payload.volume_id)?;
     let image = format!("{}/{}", album_id, image_id);
-    let file = read_file(&image).await.map_err(|e| {
+    let file = read_file(&volume.mount_path, &image).await.map_err(|e| {
         error!("Failed to read file: {:?}", e);
         Error::FailedToReadFile
-    });
+    })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("image/jpeg"),
+    );
 
-    match file {
-        Ok(file) => {
-            let mut headers = HeaderMap::new();
-            headers.insert(
-                header::CONTENT_TYPE,
-                header::HeaderValue::from_static("image/jpeg"),
-            );
+    Ok((headers, file))
+}
 
-            Ok((headers, file))
+/// Serves the untouched original of an image, if it was kept or archived.
+pub async fn get_original_image(
+    State(context): State<ApiState>,
+    Path(image_id): Path<Uuid>,
+) -> Result<impl IntoResponse, Error> {
+    let image = ImageDao::get_by_id(&context.mm, &image_id)?;
+    let archive_path = image.archive_path.ok_or(Error::OriginalNotFound)?;
+
+    let file = tokio::fs::read(&archive_path).await.map_err(|e| {
+        error!("Failed to read original {}: {:?}", archive_path, e);
+        Error::OriginalNotFound
+    })?;
+
+    let mut headers = HeaderMap::new();
+    if let Some(raw_format) = &image.raw_format {
+        let disposition = format!(
+            "attachment; filename=\"{}\"",
+            image.original_full_title.replace('"', "")
+        );
+        if let Ok(disposition) = header::HeaderValue::from_str(&disposition) {
+            headers.insert(header::CONTENT_DISPOSITION, disposition);
         }
-        Err(e) => Err(e),
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static(raw::content_type(raw_format)),
+        );
+        return Ok((headers, file));
+    }
+
+    let content_type = match image_type(&file) {
+        Ok(ImageType::Jpeg) => "image/jpeg",
+        Ok(ImageType::Png) => "image/png",
+        Ok(ImageType::Gif) => "image/gif",
+        Ok(ImageType::Webp) => "image/webp",
+        Ok(ImageType::Tiff) => "image/tiff",
+        Ok(ImageType::Heif) => "image/heif",
+        _ => "application/octet-stream",
+    };
+
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(content_type),
+    );
+
+    Ok((headers, file))
+}
+
+/// Serves a video stored outside of Lust, with range support for seeking.
+pub async fn get_media(
+    State(context): State<ApiState>,
+    Path(image_id): Path<Uuid>,
+    request: Request<Body>,
+) -> Result<impl IntoResponse, Error> {
+    let image = ImageDao::get_by_id(&context.mm, &image_id)?;
+    if image.media_type != MEDIA_VIDEO {
+        return Err(Error::MediaNotFound);
     }
+
+    let response = ServeFile::new(media_path(&image.path))
+        .oneshot(request)
+        .await
+        .map_err(|e| {
+            error!("Failed to serve media {}: {:?}", image.path, e);
+            Error::FailedToReadFile
+        })?;
+
+    Ok(response)
 }
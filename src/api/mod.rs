@@ -1,5 +1,9 @@
 pub mod api_handler;
+pub mod archive_handler;
 pub mod auth_middleware;
+pub mod avatar_handler;
 pub mod error;
 pub mod image_handler;
 pub mod login_handler;
+pub mod resumable_handler;
+pub mod upload_handler;
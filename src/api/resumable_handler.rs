@@ -0,0 +1,294 @@
+use std::io::Write;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::debug;
+use uuid::Uuid;
+
+use super::{api_handler::ApiState, error::Error};
+
+use crate::{
+    config::config,
+    domain::{
+        album::AlbumDao,
+        upload_session::{CreateUploadSession, UploadSession, UploadSessionDao},
+    },
+    ingest::{
+        ingest_image, scan_file,
+        staging::{create_staging_file, remove_staging_file, staging_path},
+        DuplicatePolicy, Error as IngestError, IngestOptions, NewImage, RetentionPolicy,
+    },
+};
+
+const UPLOAD_OFFSET: &str = "upload-offset";
+const UPLOAD_LENGTH: &str = "upload-length";
+
+#[derive(Deserialize)]
+pub struct CreateUpload {
+    album_id: Uuid,
+    file_name: String,
+    upload_length: i64,
+    is_primary_album: Option<bool>,
+    on_duplicate: Option<DuplicatePolicy>,
+    retention: Option<RetentionPolicy>,
+}
+
+#[derive(Serialize)]
+pub struct UploadStatus {
+    id: Uuid,
+    upload_offset: i64,
+    upload_length: i64,
+}
+
+impl From<&UploadSession> for UploadStatus {
+    fn from(session: &UploadSession) -> Self {
+        Self {
+            id: session.id,
+            upload_offset: session.upload_offset,
+            upload_length: session.upload_length,
+        }
+    }
+}
+
+fn offset_headers(session: &UploadSession) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(UPLOAD_OFFSET, HeaderValue::from(session.upload_offset));
+    headers.insert(UPLOAD_LENGTH, HeaderValue::from(session.upload_length));
+    headers.insert(
+        axum::http::header::CACHE_CONTROL,
+        HeaderValue::from_static("no-store"),
+    );
+    headers
+}
+
+fn staging_name(session: &UploadSession) -> String {
+    session.id.to_string()
+}
+
+pub async fn create_upload(
+    State(context): State<ApiState>,
+    Json(payload): Json<CreateUpload>,
+) -> Result<Response, Error> {
+    if payload.upload_length <= 0 {
+        return Err(Error::BadRequest("upload_length must be positive".to_string()));
+    }
+    if payload.upload_length > config().MAX_UPLOAD_SIZE as i64 {
+        return Err(Error::BadRequest(
+            "upload_length exceeds MAX_UPLOAD_SIZE".to_string(),
+        ));
+    }
+
+    let album = AlbumDao::get_by_id(&context.mm, &payload.album_id)?;
+    let session = UploadSessionDao::create(
+        &context.mm,
+        CreateUploadSession {
+            id: Uuid::new_v4(),
+            album_id: album.id,
+            file_name: payload.file_name,
+            upload_length: payload.upload_length,
+            is_primary_album: payload.is_primary_album.unwrap_or(true),
+            link_duplicates: payload.on_duplicate == Some(DuplicatePolicy::Link),
+            retention: payload
+                .retention
+                .unwrap_or(config().ORIGINALS_RETENTION)
+                .as_str()
+                .to_string(),
+        },
+    )?;
+    create_staging_file(&staging_name(&session)).await?;
+    debug!("{:<12} - created upload session {}", "RESUMABLE", session.id);
+
+    let mut headers = offset_headers(&session);
+    if let Ok(location) = HeaderValue::from_str(&format!("/api/uploads/{}", session.id)) {
+        headers.insert(axum::http::header::LOCATION, location);
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        headers,
+        Json(UploadStatus::from(&session)),
+    )
+        .into_response())
+}
+
+pub async fn get_upload_offset(
+    State(context): State<ApiState>,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Response, Error> {
+    let session = sync_offset(&context, &upload_id).await?;
+
+    Ok((StatusCode::OK, offset_headers(&session)).into_response())
+}
+
+pub async fn patch_upload(
+    State(context): State<ApiState>,
+    Path(upload_id): Path<Uuid>,
+    headers: HeaderMap,
+    chunk: Bytes,
+) -> Result<Response, Error> {
+    let offset = headers
+        .get(UPLOAD_OFFSET)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok())
+        .ok_or_else(|| Error::BadRequest("missing Upload-Offset header".to_string()))?;
+
+    // Only one request appends to a session at a time, a second one sent with the same
+    // offset gets the new offset once the first is done.
+    let (session, appended) = with_staged_file(&context, upload_id, move |session, len| {
+        if offset != len {
+            return (len, Ok(false));
+        }
+        if len + chunk.len() as i64 > session.upload_length {
+            return (
+                len,
+                Err(Error::BadRequest(
+                    "chunk exceeds declared upload_length".to_string(),
+                )),
+            );
+        }
+        if chunk.is_empty() {
+            return (len, Ok(true));
+        }
+        match append_chunk(&staging_name(session), &chunk) {
+            Ok(()) => (len + chunk.len() as i64, Ok(true)),
+            // a partly written chunk is kept, the client resumes from there
+            Err(_) => (
+                staged_len(&staging_name(session)).unwrap_or(len),
+                Err(Error::FailedToWriteFile),
+            ),
+        }
+    })
+    .await?;
+    if !appended? {
+        return Ok((StatusCode::CONFLICT, offset_headers(&session)).into_response());
+    }
+
+    if session.upload_offset < session.upload_length {
+        return Ok((StatusCode::NO_CONTENT, offset_headers(&session)).into_response());
+    }
+
+    complete_upload(&context, session).await
+}
+
+pub async fn delete_upload(
+    State(context): State<ApiState>,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Response, Error> {
+    let session = UploadSessionDao::get_by_id(&context.mm, &upload_id)?;
+    UploadSessionDao::delete(&context.mm, &session.id)?;
+    remove_staging_file(&staging_name(&session)).await;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+async fn sync_offset(context: &ApiState, upload_id: &Uuid) -> Result<UploadSession, Error> {
+    let (session, ()) = with_staged_file(context, *upload_id, |_, len| (len, ())).await?;
+    Ok(session)
+}
+
+// The staging file is the source of truth for the offset, so a session
+// survives a crash between appending a chunk and saving the new offset.
+// `f` gets the length of the staged file and runs under the session's row lock,
+// the offset it returns is saved.
+async fn with_staged_file<T: Send + 'static>(
+    context: &ApiState,
+    upload_id: Uuid,
+    f: impl FnOnce(&UploadSession, i64) -> (i64, Result<T, Error>) + Send + 'static,
+) -> Result<(UploadSession, Result<T, Error>), Error> {
+    let mm = context.mm.clone();
+    tokio::task::spawn_blocking(move || {
+        UploadSessionDao::update_offset_locked(&mm, &upload_id, |session| {
+            match staged_len(&staging_name(session)) {
+                Ok(len) => f(session, len),
+                Err(_) => (session.upload_offset, Err(Error::FailedToWriteFile)),
+            }
+        })
+    })
+    .await
+    .map_err(|e| Error::ServiceError(e.to_string()))?
+    .map_err(Into::into)
+}
+
+/// Length of the staging file, which is created if it went missing.
+fn staged_len(name: &str) -> std::io::Result<i64> {
+    let path = staging_path(name);
+    match std::fs::metadata(&path) {
+        Ok(meta) => Ok(meta.len() as i64),
+        Err(_) => {
+            std::fs::create_dir_all(&config().UPLOAD_STAGING_DIR)?;
+            std::fs::File::create(&path)?;
+            Ok(0)
+        }
+    }
+}
+
+fn append_chunk(name: &str, chunk: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(staging_path(name))?;
+    file.write_all(chunk)?;
+    file.flush()
+}
+
+async fn complete_upload(context: &ApiState, session: UploadSession) -> Result<Response, Error> {
+    let name = staging_name(&session);
+    let album = AlbumDao::get_by_id(&context.mm, &session.album_id)?;
+    let file = scan_file(staging_path(&name)).await?;
+
+    let options = IngestOptions {
+        album_id: album.id,
+        is_primary_album: session.is_primary_album,
+        on_duplicate: match session.link_duplicates {
+            true => DuplicatePolicy::Link,
+            false => DuplicatePolicy::Reject,
+        },
+        retention: session.retention.parse().unwrap_or_default(),
+    };
+
+    let res = ingest_image(
+        &context.mm,
+        context.store.as_ref(),
+        &options,
+        NewImage {
+            title: session.file_name.clone(),
+            original_full_title: format!("{}/{}", album.original_title, session.file_name),
+            file,
+            order_index: None,
+            source: None,
+            sidecar: None,
+        },
+    )
+    .await;
+
+    match res {
+        Ok(ingested) => {
+            UploadSessionDao::delete(&context.mm, &session.id)?;
+            remove_staging_file(&name).await;
+            Ok((
+                StatusCode::OK,
+                offset_headers(&session),
+                Json(json!({
+                    "success": true,
+                    "image_id": ingested.image.id,
+                    "linked": ingested.linked,
+                })),
+            )
+                .into_response())
+        }
+        // transient failures keep the staged file, so the client can retry
+        // completion with an empty PATCH at the final offset
+        Err(e @ IngestError::ServiceError(_)) | Err(e @ IngestError::DbError(_)) => Err(e.into()),
+        Err(e) => {
+            UploadSessionDao::delete(&context.mm, &session.id)?;
+            remove_staging_file(&name).await;
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use axum::{
+    extract::{multipart::Field, Multipart, Path, Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use super::{api_handler::ApiState, error::Error};
+
+use crate::{
+    config::config,
+    domain::album::AlbumDao,
+    ingest::{
+        ingest_image,
+        scan::Spool,
+        staging::{create_staging_file, open_staging_file, remove_staging_file, staging_path},
+        DuplicatePolicy, IngestOptions, NewImage, RetentionPolicy, ScannedFile,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct UploadParams {
+    is_primary_album: Option<bool>,
+    on_duplicate: Option<DuplicatePolicy>,
+    retention: Option<RetentionPolicy>,
+}
+
+#[derive(Serialize)]
+pub struct UploadResult {
+    pub file: String,
+    pub image_id: Option<Uuid>,
+    pub linked: bool,
+    pub error: Option<String>,
+}
+
+pub async fn upload_album_images(
+    State(context): State<ApiState>,
+    Path(album_id): Path<Uuid>,
+    Query(params): Query<UploadParams>,
+    mut multipart: Multipart,
+) -> Result<Json<Vec<UploadResult>>, Error> {
+    let album = AlbumDao::get_by_id(&context.mm, &album_id)?;
+    let options = IngestOptions {
+        album_id: album.id,
+        is_primary_album: params.is_primary_album.unwrap_or(true),
+        on_duplicate: params.on_duplicate.unwrap_or_default(),
+        retention: params.retention.unwrap_or(config().ORIGINALS_RETENTION),
+    };
+
+    let mut results = Vec::new();
+    loop {
+        let field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) if results.is_empty() => return Err(Error::BadRequest(e.body_text())),
+            // the files handled so far are reported, the rest of the body is lost
+            Err(e) => {
+                error!(
+                    "{:<12} - failed to read upload: {}",
+                    "UPLOAD",
+                    e.body_text()
+                );
+                break;
+            }
+        };
+        let file_name = match field.file_name() {
+            Some(file_name) => file_name.to_string(),
+            None => continue,
+        };
+        debug!("{:<12} - uploading {}", "UPLOAD", file_name);
+
+        let name = format!("multipart-{}", Uuid::new_v4());
+        let file = match spool_field(&name, field).await {
+            Ok(file) => file,
+            Err(e) => {
+                error!("{:<12} - failed to receive {}: {}", "UPLOAD", file_name, e);
+                remove_staging_file(&name).await;
+                results.push(UploadResult {
+                    file: file_name,
+                    image_id: None,
+                    linked: false,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+
+        let res = ingest_image(
+            &context.mm,
+            context.store.as_ref(),
+            &options,
+            NewImage {
+                title: file_name.clone(),
+                original_full_title: format!("{}/{}", album.original_title, file_name),
+                file,
+                order_index: None,
+                source: None,
+                sidecar: None,
+            },
+        )
+        .await;
+        remove_staging_file(&name).await;
+
+        match res {
+            Ok(ingested) => results.push(UploadResult {
+                file: file_name,
+                image_id: Some(ingested.image.id),
+                linked: ingested.linked,
+                error: None,
+            }),
+            Err(e) => {
+                error!("{:<12} - failed to upload {}: {}", "UPLOAD", file_name, e);
+                results.push(UploadResult {
+                    file: file_name,
+                    image_id: None,
+                    linked: false,
+                    error: Some(e.to_string()),
+                })
+            }
+        }
+    }
+
+    Ok(Json(results))
+}
+
+/// Writes the field to the staging dir as it arrives instead of buffering it.
+async fn spool_field(name: &str, mut field: Field<'_>) -> Result<ScannedFile, Error> {
+    create_staging_file(name).await?;
+    let mut spool = Spool::new(open_staging_file(name).await?, staging_path(name));
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| Error::BadRequest(e.body_text()))?
+    {
+        spool.write(&chunk).await?;
+    }
+    Ok(spool.finish().await?)
+}
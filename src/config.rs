@@ -1,5 +1,7 @@
 use std::{str::FromStr, sync::OnceLock};
 
+use crate::ingest::RetentionPolicy;
+use crate::services::store::StorageBackend;
 use crate::web::crypt::base64::b64u_decode;
 
 pub fn config() -> &'static Config {
@@ -19,20 +21,114 @@ pub struct Config {
     pub TOKEN_DURATION: i64,
     pub PORT: i32,
     pub LUST_BUCKET: String,
+    pub MAX_UPLOAD_SIZE: usize,
+    pub UPLOAD_STAGING_DIR: String,
+    pub UPLOAD_SESSION_TTL_SECS: u64,
+    pub IMPORT_WORKERS: usize,
+    pub UPLOAD_CONCURRENCY: usize,
+    pub ORIGINALS_RETENTION: RetentionPolicy,
+    pub ARCHIVE_DIR: String,
+    pub ARCHIVE_MAX_EXTRACTED_SIZE: u64,
+    pub ARCHIVE_MAX_ENTRIES: usize,
+    pub LUST_CLEANUP_INTERVAL: u64,
+    pub LUST_STORAGE_DIR: Option<String>,
+    pub LUST_CONNECT_TIMEOUT_SECS: u64,
+    pub LUST_RESPONSE_TIMEOUT_SECS: u64,
+    pub LUST_UPLOAD_MIN_KBPS: u64,
+    pub LUST_MAX_RETRIES: u32,
+    pub LUST_RETRY_BASE_MS: u64,
+    pub LUST_RETRY_MAX_MS: u64,
+    pub LUST_BREAKER_THRESHOLD: u32,
+    pub LUST_BREAKER_COOLDOWN_SECS: u64,
+    pub WATCH_IMAGES_DIR: bool,
+    pub WATCH_SETTLE_SECS: u64,
+    pub WATCH_IGNORE: Vec<String>,
+    pub MEDIA_DIR: String,
+    pub STORAGE_BACKEND: StorageBackend,
+    pub LOCAL_STORE_DIR: String,
+    pub S3_ENDPOINT: Option<String>,
+    pub S3_BUCKET: String,
+    pub S3_REGION: String,
+    pub S3_ACCESS_KEY: Option<String>,
+    pub S3_SECRET_KEY: Option<String>,
+    pub S3_PATH_STYLE: bool,
+    pub S3_STORE_RENDITIONS: bool,
+    pub SCRUB_INTERVAL: u64,
+    pub SCRUB_BATCH_SIZE: i64,
+    pub ORPHAN_GRACE_SECS: i64,
 }
 
 impl Config {
     fn load_from_env() -> Config {
+        let images_dir = get_env("IMAGES_DIR");
+        let upload_staging_dir =
+            get_env_opt_parse_or("UPLOAD_STAGING_DIR", format!("{}/.staging", images_dir));
+        let archive_dir = get_env_opt_parse_or("ARCHIVE_DIR", format!("{}/.archive", images_dir));
+        let media_dir = get_env_opt_parse_or("MEDIA_DIR", format!("{}/.media", images_dir));
+        let local_store_dir =
+            get_env_opt_parse_or("LOCAL_STORE_DIR", format!("{}/.store", images_dir));
+        let storage_backend = get_env_opt_parse_or("STORAGE_BACKEND", StorageBackend::default());
+        // Lust and S3 are only required when they are the storage backend
+        let lust_url = match storage_backend {
+            StorageBackend::Lust => get_env("LUST_URL"),
+            _ => std::env::var("LUST_URL").unwrap_or_default(),
+        };
+        let s3_endpoint = match storage_backend {
+            StorageBackend::S3 => Some(get_env("S3_ENDPOINT")),
+            _ => std::env::var("S3_ENDPOINT").ok(),
+        };
+
         Config {
             DB_URL: get_env("DATABASE_URL"),
-            IMAGES_DIR: get_env("IMAGES_DIR"),
-            LUST_URL: get_env("LUST_URL"),
+            IMAGES_DIR: images_dir,
+            LUST_URL: lust_url,
             LUST_PROFILE_BUCKET: get_env("LUST_PROFILE_BUCKET"),
             TOKEN_SECRET: get_env_b64u_as_u8s("TOKEN_SECRET"),
             PWD_KEY: get_env_b64u_as_u8s("PWD_KEY"),
             TOKEN_DURATION: get_env_parse_or("TOKEN_DURATION", None),
             PORT: get_env_parse_or("PORT", Some(3000)),
             LUST_BUCKET: get_env("LUST_BUCKET"),
+            MAX_UPLOAD_SIZE: get_env_opt_parse_or("MAX_UPLOAD_SIZE", 1024 * 1024 * 100),
+            UPLOAD_STAGING_DIR: upload_staging_dir,
+            UPLOAD_SESSION_TTL_SECS: get_env_opt_parse_or("UPLOAD_SESSION_TTL_SECS", 86400),
+            IMPORT_WORKERS: get_env_opt_parse_or("IMPORT_WORKERS", 2),
+            UPLOAD_CONCURRENCY: get_env_opt_parse_or("UPLOAD_CONCURRENCY", 4),
+            ORIGINALS_RETENTION: get_env_opt_parse_or(
+                "ORIGINALS_RETENTION",
+                RetentionPolicy::default(),
+            ),
+            ARCHIVE_DIR: archive_dir,
+            ARCHIVE_MAX_EXTRACTED_SIZE: get_env_opt_parse_or(
+                "ARCHIVE_MAX_EXTRACTED_SIZE",
+                1024 * 1024 * 1024,
+            ),
+            ARCHIVE_MAX_ENTRIES: get_env_opt_parse_or("ARCHIVE_MAX_ENTRIES", 10_000),
+            LUST_CLEANUP_INTERVAL: get_env_opt_parse_or("LUST_CLEANUP_INTERVAL", 300),
+            LUST_STORAGE_DIR: std::env::var("LUST_STORAGE_DIR").ok(),
+            LUST_CONNECT_TIMEOUT_SECS: get_env_opt_parse_or("LUST_CONNECT_TIMEOUT_SECS", 5),
+            LUST_RESPONSE_TIMEOUT_SECS: get_env_opt_parse_or("LUST_RESPONSE_TIMEOUT_SECS", 60),
+            LUST_UPLOAD_MIN_KBPS: get_env_opt_parse_or("LUST_UPLOAD_MIN_KBPS", 256),
+            LUST_MAX_RETRIES: get_env_opt_parse_or("LUST_MAX_RETRIES", 3),
+            LUST_RETRY_BASE_MS: get_env_opt_parse_or("LUST_RETRY_BASE_MS", 200),
+            LUST_RETRY_MAX_MS: get_env_opt_parse_or("LUST_RETRY_MAX_MS", 5000),
+            LUST_BREAKER_THRESHOLD: get_env_opt_parse_or("LUST_BREAKER_THRESHOLD", 5),
+            LUST_BREAKER_COOLDOWN_SECS: get_env_opt_parse_or("LUST_BREAKER_COOLDOWN_SECS", 30),
+            WATCH_IMAGES_DIR: get_env_opt_parse_or("WATCH_IMAGES_DIR", false),
+            WATCH_SETTLE_SECS: get_env_opt_parse_or("WATCH_SETTLE_SECS", 30),
+            WATCH_IGNORE: get_env_list("WATCH_IGNORE"),
+            MEDIA_DIR: media_dir,
+            STORAGE_BACKEND: storage_backend,
+            LOCAL_STORE_DIR: local_store_dir,
+            S3_ENDPOINT: s3_endpoint,
+            S3_BUCKET: get_env_opt_parse_or("S3_BUCKET", "album-storage".to_string()),
+            S3_REGION: get_env_opt_parse_or("S3_REGION", "us-east-1".to_string()),
+            S3_ACCESS_KEY: std::env::var("S3_ACCESS_KEY").ok(),
+            S3_SECRET_KEY: std::env::var("S3_SECRET_KEY").ok(),
+            S3_PATH_STYLE: get_env_opt_parse_or("S3_PATH_STYLE", true),
+            S3_STORE_RENDITIONS: get_env_opt_parse_or("S3_STORE_RENDITIONS", true),
+            SCRUB_INTERVAL: get_env_opt_parse_or("SCRUB_INTERVAL", 3600),
+            SCRUB_BATCH_SIZE: get_env_opt_parse_or("SCRUB_BATCH_SIZE", 500),
+            ORPHAN_GRACE_SECS: get_env_opt_parse_or("ORPHAN_GRACE_SECS", 3600),
         }
     }
 }
@@ -55,6 +151,27 @@ where
     }
 }
 
+fn get_env_opt_parse_or<T>(name: &'static str, or: T) -> T
+where
+    T: FromStr,
+{
+    std::env::var(name)
+        .ok()
+        .and_then(|val| val.parse::<T>().ok())
+        .unwrap_or(or)
+}
+
+fn get_env_list(name: &'static str) -> Vec<String> {
+    std::env::var(name)
+        .map(|val| {
+            val.split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn get_env_b64u_as_u8s(name: &'static str) -> Vec<u8> {
     b64u_decode(&get_env(name)).unwrap_or_else(|_| panic!("{} is not a valid b64u", name))
 }
@@ -18,6 +18,7 @@ pub struct Album {
     pub prev_image_id: Option<Uuid>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub parent_id: Option<Uuid>,
 }
 
 #[derive(Insertable, Serialize, Debug)]
@@ -27,6 +28,7 @@ pub struct CreateAlbum {
     pub title: String,
     pub description: Option<String>,
     pub original_title: String,
+    pub parent_id: Option<Uuid>,
 }
 
 #[derive(AsChangeset, Insertable, Serialize, Debug)]
@@ -62,6 +64,32 @@ impl AlbumDao {
             .map_err(Into::into)
     }
 
+    pub fn get_by_title(mm: &ModelManager, title: &str) -> Result<Option<Album>> {
+        let mut conn = mm.conn()?;
+
+        album::dsl::album
+            .filter(album::dsl::title.eq(title))
+            .first::<Album>(&mut conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Titles are only unique among the children of an album.
+    pub fn get_by_title_and_parent(
+        mm: &ModelManager,
+        title: &str,
+        parent_id: &Uuid,
+    ) -> Result<Option<Album>> {
+        let mut conn = mm.conn()?;
+
+        album::dsl::album
+            .filter(album::dsl::title.eq(title))
+            .filter(album::dsl::parent_id.eq(parent_id))
+            .first::<Album>(&mut conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
     pub fn list(mm: &ModelManager) -> Result<Vec<Album>> {
         let mut conn = mm.conn()?;
 
@@ -70,6 +98,16 @@ impl AlbumDao {
             .map_err(Into::into)
     }
 
+    pub fn get_children(mm: &ModelManager, parent_id: &Uuid) -> Result<Vec<Album>> {
+        let mut conn = mm.conn()?;
+
+        album::dsl::album
+            .filter(album::dsl::parent_id.eq(parent_id))
+            .order(album::dsl::title.asc())
+            .load::<Album>(&mut conn)
+            .map_err(Into::into)
+    }
+
     pub fn update(mm: &ModelManager, id: &Uuid, update_album: UpdateAlbum) -> Result<Album> {
         let mut conn = mm.conn()?;
 
@@ -26,6 +26,7 @@ pub struct Album {
     pub prev_image_id: Option<Uuid>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub parent_id: Option<Uuid>,
 }
 
 #[ComplexObject]
@@ -56,6 +57,34 @@ impl Album {
         };
         Ok(image)
     }
+
+    async fn parent(&self, ctx: &Context<'_>) -> Result<Option<Album>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let parent = match self.parent_id {
+            Some(parent_id) => {
+                let album =
+                    AlbumDao::get_by_id(mm, &parent_id).map_err(|e| -> Error { e.into() })?;
+                Some(album.into())
+            }
+            None => None,
+        };
+        Ok(parent)
+    }
+
+    async fn children(&self, ctx: &Context<'_>) -> Result<Vec<Album>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let albums = AlbumDao::get_children(mm, &self.id.to_uuid())
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(albums.into_iter().map(|album| album.into()).collect())
+    }
 }
 
 impl From<DbAlbum> for Album {
@@ -69,6 +98,7 @@ impl From<DbAlbum> for Album {
             prev_image_id: album.prev_image_id,
             created_at: album.created_at,
             updated_at: album.updated_at,
+            parent_id: album.parent_id,
         }
     }
 }
@@ -106,6 +136,7 @@ pub struct CreateAlbum {
     pub description: Option<String>,
     pub original_title: String,
     pub photoed_at: Option<chrono::NaiveDateTime>,
+    pub parent_id: Option<Uuid>,
 }
 
 impl From<CreateAlbum> for DbCreateAlbum {
@@ -115,6 +146,7 @@ impl From<CreateAlbum> for DbCreateAlbum {
             title: val.title,
             description: val.description,
             original_title: val.original_title,
+            parent_id: val.parent_id,
         }
     }
 }
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+
+use futures_util::{stream, StreamExt};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::user::UserBmc;
+use crate::graphql::{Error, Result};
+use crate::ingest::compensate_upload;
+use crate::services::{
+    error::Error as ServiceError,
+    store::{decode_location, encode_path, ImageStore},
+};
+
+use super::{ConsistencyDao, ConsistencyReport};
+
+pub async fn check_consistency(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    repair: bool,
+) -> Result<ConsistencyReport> {
+    let images = ConsistencyDao::list_image_paths(mm)?;
+
+    let missing_in_lust = find_missing_in_lust(store, &images).await?;
+    let avatars = UserBmc::list_avatar_paths(mm)?;
+    let orphaned_in_lust = find_orphaned_in_lust(store, &images, &avatars).await?;
+    let stale_orphans_in_lust = match &orphaned_in_lust {
+        Some(orphaned) => {
+            let seen_before = chrono::Utc::now().naive_utc()
+                - chrono::Duration::seconds(config().ORPHAN_GRACE_SECS);
+            ConsistencyDao::record_orphans(mm, orphaned, seen_before)?
+        }
+        None => Vec::new(),
+    };
+    let dangling_album_images = ConsistencyDao::list_dangling_album_images(mm)?;
+    let invalid_album_covers = ConsistencyDao::list_invalid_album_covers(mm)?;
+
+    info!(
+        "{:<12} - missing in lust: {}, orphaned in lust: {} ({} stale), dangling album images: {}, invalid covers: {}",
+        "CONSISTENCY",
+        missing_in_lust.len(),
+        orphaned_in_lust.as_ref().map(|o| o.len()).unwrap_or(0),
+        stale_orphans_in_lust.len(),
+        dangling_album_images.len(),
+        invalid_album_covers.len()
+    );
+
+    let report = ConsistencyReport {
+        missing_in_lust,
+        orphaned_in_lust,
+        stale_orphans_in_lust,
+        dangling_album_images,
+        invalid_album_covers,
+        repaired: repair,
+    };
+
+    if repair {
+        repair_findings(mm, store, &report).await?;
+    }
+
+    Ok(report)
+}
+
+async fn find_missing_in_lust(
+    store: &dyn ImageStore,
+    images: &[(Uuid, String)],
+) -> Result<Vec<Uuid>> {
+    let bucket = &config().LUST_BUCKET;
+    let checks =
+        stream::iter(images)
+            .map(|(id, path)| async move {
+                store.exists(bucket, path).await.map(|exists| (*id, exists))
+            })
+            .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+    let mut missing = Vec::new();
+    for check in checks {
+        // A failing store must not make every image look missing.
+        let (id, exists) = check.map_err(|e| Error::GraphQlError(e.into()))?;
+        if !exists {
+            missing.push(id);
+        }
+    }
+    Ok(missing)
+}
+
+/// Any image the store holds that no `image` row points at is an orphan.
+/// Lust lists every bucket, so avatars from the profile bucket and images migrated
+/// to other buckets count as known too.
+/// `None` when the store can't list its images.
+async fn find_orphaned_in_lust(
+    store: &dyn ImageStore,
+    images: &[(Uuid, String)],
+    avatars: &[String],
+) -> Result<Option<Vec<String>>> {
+    let stored = match store.list(&config().LUST_BUCKET).await {
+        Ok(stored) => stored,
+        Err(ServiceError::Unsupported(e)) => {
+            info!("{:<12} - skipping orphan check: {}", "CONSISTENCY", e);
+            return Ok(None);
+        }
+        Err(e) => return Err(Error::GraphQlError(e.into())),
+    };
+
+    let known = images
+        .iter()
+        .map(|(_, path)| path)
+        .chain(avatars.iter())
+        .map(|path| {
+            let (backend, _, key) = decode_location(path);
+            encode_path(backend, key)
+        })
+        .collect::<HashSet<String>>();
+    let mut orphaned = stored
+        .into_iter()
+        .filter(|id| !known.contains(id))
+        .collect::<Vec<String>>();
+    orphaned.sort();
+    Ok(Some(orphaned))
+}
+
+async fn repair_findings(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    report: &ConsistencyReport,
+) -> Result<()> {
+    if !report.dangling_album_images.is_empty() {
+        ConsistencyDao::delete_album_images(mm, &report.dangling_album_images)?;
+    }
+
+    let mut albums = report.invalid_album_covers.clone();
+    if !report.missing_in_lust.is_empty() {
+        albums.extend(ConsistencyDao::delete_images(mm, &report.missing_in_lust)?);
+    }
+    if !albums.is_empty() {
+        albums.sort();
+        albums.dedup();
+        ConsistencyDao::reset_album_covers(mm, &albums)?;
+    }
+
+    // Failed deletes are recorded for the Lust cleanup task to retry.
+    for lust_image_id in &report.stale_orphans_in_lust {
+        compensate_upload(mm, store, &config().LUST_BUCKET, lust_image_id).await;
+    }
+    if !report.stale_orphans_in_lust.is_empty() {
+        ConsistencyDao::delete_orphan_sightings(mm, &report.stale_orphans_in_lust)?;
+    }
+
+    Ok(())
+}
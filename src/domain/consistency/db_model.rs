@@ -0,0 +1,170 @@
+use diesel::dsl::{exists, not};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::{Error, ModelManager, Result};
+use crate::domain::image::MEDIA_IMAGE;
+use crate::schema::{album, album_image, image, orphan_sighting};
+
+pub struct ConsistencyDao;
+
+impl ConsistencyDao {
+    pub fn list_image_paths(mm: &ModelManager) -> Result<Vec<(Uuid, String)>> {
+        let mut conn = mm.conn()?;
+
+        image::dsl::image
+            .filter(image::dsl::media_type.eq(MEDIA_IMAGE))
+            .select((image::dsl::id, image::dsl::path))
+            .load::<(Uuid, String)>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// `album_image` rows pointing at an album or image that no longer exists.
+    pub fn list_dangling_album_images(mm: &ModelManager) -> Result<Vec<Uuid>> {
+        let mut conn = mm.conn()?;
+
+        album_image::table
+            .left_join(album::table)
+            .left_join(image::table)
+            .filter(
+                album::dsl::id
+                    .nullable()
+                    .is_null()
+                    .or(image::dsl::id.nullable().is_null()),
+            )
+            .select(album_image::dsl::id)
+            .load::<Uuid>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Albums whose cover image isn't one of the album's images.
+    pub fn list_invalid_album_covers(mm: &ModelManager) -> Result<Vec<Uuid>> {
+        let mut conn = mm.conn()?;
+
+        album::dsl::album
+            .filter(album::dsl::prev_image_id.is_not_null())
+            .filter(not(exists(
+                album_image::dsl::album_image
+                    .filter(album_image::dsl::album_id.eq(album::dsl::id))
+                    .filter(
+                        album_image::dsl::image_id
+                            .nullable()
+                            .eq(album::dsl::prev_image_id),
+                    ),
+            )))
+            .select(album::dsl::id)
+            .load::<Uuid>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn delete_album_images(mm: &ModelManager, ids: &[Uuid]) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(
+            album_image::dsl::album_image.filter(album_image::dsl::id.eq_any(ids.to_vec())),
+        )
+        .execute(&mut conn)
+        .map_err(Into::into)
+    }
+
+    /// Points each album's cover at its first image, or clears it for empty albums.
+    pub fn reset_album_covers(mm: &ModelManager, album_ids: &[Uuid]) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            for album_id in album_ids {
+                let first_image = album_image::dsl::album_image
+                    .filter(album_image::dsl::album_id.eq(album_id))
+                    .order(album_image::dsl::order_index.asc())
+                    .select(album_image::dsl::image_id)
+                    .first::<Uuid>(conn)
+                    .optional()?;
+
+                diesel::update(album::dsl::album.find(album_id))
+                    .set((
+                        album::dsl::prev_image_id.eq(first_image),
+                        album::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(album_ids.len())
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+    }
+
+    /// Records the orphans found by this run and forgets the ones that aren't anymore.
+    /// Returns those first seen before `seen_before`.
+    pub fn record_orphans(
+        mm: &ModelManager,
+        paths: &[String],
+        seen_before: chrono::NaiveDateTime,
+    ) -> Result<Vec<String>> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            diesel::delete(
+                orphan_sighting::dsl::orphan_sighting
+                    .filter(not(orphan_sighting::dsl::path.eq_any(paths.to_vec()))),
+            )
+            .execute(conn)?;
+
+            let now = chrono::Utc::now().naive_utc();
+            diesel::insert_into(orphan_sighting::table)
+                .values(
+                    paths
+                        .iter()
+                        .map(|path| {
+                            (
+                                orphan_sighting::dsl::path.eq(path),
+                                orphan_sighting::dsl::first_seen_at.eq(now),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+
+            orphan_sighting::dsl::orphan_sighting
+                .filter(orphan_sighting::dsl::first_seen_at.lt(seen_before))
+                .select(orphan_sighting::dsl::path)
+                .load::<String>(conn)
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+    }
+
+    pub fn delete_orphan_sightings(mm: &ModelManager, paths: &[String]) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(
+            orphan_sighting::dsl::orphan_sighting
+                .filter(orphan_sighting::dsl::path.eq_any(paths.to_vec())),
+        )
+        .execute(&mut conn)
+        .map_err(Into::into)
+    }
+
+    /// Deletes image rows, unsetting them as album covers first.
+    /// Returns the albums whose cover was unset.
+    pub fn delete_images(mm: &ModelManager, ids: &[Uuid]) -> Result<Vec<Uuid>> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let albums = diesel::update(
+                album::dsl::album.filter(
+                    album::dsl::prev_image_id
+                        .assume_not_null()
+                        .eq_any(ids.to_vec()),
+                ),
+            )
+            .set(album::dsl::prev_image_id.eq(None::<Uuid>))
+            .returning(album::dsl::id)
+            .get_results::<Uuid>(conn)?;
+
+            diesel::delete(image::dsl::image.filter(image::dsl::id.eq_any(ids.to_vec())))
+                .execute(conn)?;
+
+            Ok(albums)
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+    }
+}
@@ -0,0 +1,19 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+#[derive(SimpleObject, Default, Debug)]
+pub struct ConsistencyReport {
+    /// Images whose `path` no longer exists in the Lust bucket.
+    pub missing_in_lust: Vec<Uuid>,
+    /// Lust images without an `image` row. Only checked when `LUST_STORAGE_DIR`
+    /// points at the storage directory of the image bucket.
+    pub orphaned_in_lust: Option<Vec<String>>,
+    /// Orphans already found by a run at least `ORPHAN_GRACE_SECS` ago, only these are
+    /// deleted by a repair. Newer ones may still be uploads about to get their row.
+    pub stale_orphans_in_lust: Vec<String>,
+    /// `album_image` rows pointing at a missing album or image.
+    pub dangling_album_images: Vec<Uuid>,
+    /// Albums whose cover image isn't part of the album.
+    pub invalid_album_covers: Vec<Uuid>,
+    pub repaired: bool,
+}
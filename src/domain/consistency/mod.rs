@@ -0,0 +1,9 @@
+mod check;
+mod db_model;
+mod graphql_model;
+mod mutation;
+
+pub use check::check_consistency;
+pub use db_model::ConsistencyDao;
+pub use graphql_model::ConsistencyReport;
+pub use mutation::ConsistencyMutation;
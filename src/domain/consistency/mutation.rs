@@ -0,0 +1,36 @@
+use async_graphql::*;
+
+use crate::{
+    db::ModelManager,
+    graphql::{AdminGuard, Error},
+    services::store::Store,
+};
+
+use super::{check_consistency, ConsistencyReport};
+
+#[derive(Default)]
+pub struct ConsistencyMutation;
+
+#[Object]
+impl ConsistencyMutation {
+    /// Cross-checks Postgres, Lust and the filesystem, repairing what it finds when `repair` is set.
+    /// Orphaned objects are only deleted once an earlier run has seen them, see `staleOrphansInLust`.
+    #[graphql(guard = "AdminGuard")]
+    async fn check_consistency(
+        &self,
+        ctx: &Context<'_>,
+        repair: bool,
+    ) -> Result<ConsistencyReport> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
+        };
+
+        Ok(check_consistency(mm, store, repair).await?)
+    }
+}
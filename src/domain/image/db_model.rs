@@ -7,6 +7,14 @@ use crate::domain::album::DbAlbum;
 use crate::domain::album_image_options::DbCreateAlbumImage;
 use crate::schema::{album, album_image, image};
 
+pub const MEDIA_IMAGE: &str = "image";
+pub const MEDIA_VIDEO: &str = "video";
+
+pub const INTEGRITY_UNVERIFIED: &str = "unverified";
+pub const INTEGRITY_OK: &str = "ok";
+pub const INTEGRITY_CORRUPT: &str = "corrupt";
+pub const INTEGRITY_MISSING: &str = "missing";
+
 #[derive(Queryable, Deserialize, Debug)]
 #[diesel(table_name = image)]
 pub struct Image {
@@ -20,6 +28,18 @@ pub struct Image {
     pub is_uploaded: bool,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub content_hash: Option<String>,
+    pub archive_path: Option<String>,
+    pub media_type: String,
+    pub paired_image_id: Option<Uuid>,
+    pub raw_format: Option<String>,
+    pub volume_id: Option<Uuid>,
+    /// Checksum Lust reported for the upload.
+    pub lust_checksum: Option<i64>,
+    /// Hash of the stored object as the store serves it, what scrubbing verifies against.
+    pub stored_hash: Option<String>,
+    pub integrity_status: String,
+    pub integrity_checked_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Insertable, Serialize, Debug)]
@@ -33,6 +53,15 @@ pub struct CreateImage {
     pub width: Option<i32>,
     pub height: Option<i32>,
     pub is_uploaded: bool,
+    pub content_hash: Option<String>,
+    pub archive_path: Option<String>,
+    pub media_type: String,
+    pub raw_format: Option<String>,
+    /// Defaults to now when not set.
+    pub created_at: Option<chrono::NaiveDateTime>,
+    pub volume_id: Option<Uuid>,
+    pub lust_checksum: Option<i64>,
+    pub stored_hash: Option<String>,
 }
 
 #[derive(AsChangeset, Insertable, Serialize, Debug)]
@@ -176,6 +205,108 @@ impl ImageDao {
         }
     }
 
+    pub fn get_by_content_hash(mm: &ModelManager, content_hash: &str) -> Result<Option<Image>> {
+        let mut conn = mm.conn()?;
+
+        image::dsl::image
+            .filter(image::dsl::content_hash.eq(content_hash))
+            .first::<Image>(&mut conn)
+            .optional()
+            .map_err(|e| e.into())
+    }
+
+    pub fn link_to_album(
+        mm: &ModelManager,
+        album_id: &Uuid,
+        image_id: &Uuid,
+        order_index: Option<i32>,
+        is_primary_album: bool,
+    ) -> Result<Image> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let res = image::dsl::image
+                .find(image_id)
+                .first::<Image>(conn)
+                .map_err(|e| -> Error { e.into() })?;
+
+            let is_linked = album_image::dsl::album_image
+                .filter(album_image::dsl::album_id.eq(album_id))
+                .filter(album_image::dsl::image_id.eq(image_id))
+                .count()
+                .get_result::<i64>(conn)
+                .map_err(|e| -> Error { e.into() })?
+                > 0;
+
+            if !is_linked {
+                let size = match order_index {
+                    Some(order_index) => order_index,
+                    None => album_image::dsl::album_image
+                        .filter(album_image::dsl::album_id.eq(album_id))
+                        .count()
+                        .get_result::<i64>(conn)
+                        .map(|c| c as i32)
+                        .map_err(|e| -> Error { e.into() })?,
+                };
+
+                let new_album_image = DbCreateAlbumImage {
+                    id: Uuid::new_v4(),
+                    album_id: *album_id,
+                    image_id: *image_id,
+                    order_index: size,
+                    highlighted: false,
+                    is_primary_album,
+                };
+
+                diesel::insert_into(album_image::dsl::album_image)
+                    .values(&new_album_image)
+                    .execute(conn)?;
+            }
+
+            Ok(res)
+        })
+    }
+
+    /// Pairs the image with the other half of a Live Photo: an image of the other
+    /// media type in the same album that shares its base name.
+    pub fn pair_live_photo(
+        mm: &ModelManager,
+        album_id: &Uuid,
+        new_image: &Image,
+    ) -> Result<Option<Uuid>> {
+        let mut conn = mm.conn()?;
+        let stem = base_name(&new_image.title);
+
+        conn.transaction(|conn| {
+            let candidates = album_image::dsl::album_image
+                .filter(album_image::dsl::album_id.eq(album_id))
+                .inner_join(image::dsl::image)
+                .filter(image::dsl::id.ne(new_image.id))
+                .filter(image::dsl::media_type.ne(&new_image.media_type))
+                .filter(image::dsl::paired_image_id.is_null())
+                .select(image::all_columns)
+                .load::<Image>(conn)?;
+
+            let pair = candidates
+                .into_iter()
+                .find(|candidate| base_name(&candidate.title).eq_ignore_ascii_case(stem));
+            let pair = match pair {
+                Some(pair) => pair,
+                None => return Ok(None),
+            };
+
+            diesel::update(image::dsl::image.find(new_image.id))
+                .set(image::dsl::paired_image_id.eq(pair.id))
+                .execute(conn)?;
+            diesel::update(image::dsl::image.find(pair.id))
+                .set(image::dsl::paired_image_id.eq(new_image.id))
+                .execute(conn)?;
+
+            Ok(Some(pair.id))
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+    }
+
     pub fn get_albums(mm: &ModelManager, image_id: &Uuid) -> Result<Vec<DbAlbum>> {
         let mut conn = mm.conn()?;
 
@@ -187,3 +318,7 @@ impl ImageDao {
             .map_err(|e| e.into())
     }
 }
+
+fn base_name(title: &str) -> &str {
+    title.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(title)
+}
@@ -1,4 +1,4 @@
-use async_graphql::{ComplexObject, Context, InputObject, SimpleObject};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, SimpleObject};
 use async_graphql_relay::{RelayNode, RelayNodeID, RelayNodeObject};
 use async_trait::async_trait;
 use uuid::Uuid;
@@ -7,10 +7,62 @@ use crate::{
     db::ModelManager,
     domain::album::{Album, DbAlbum},
     domain::album_image_options::{AlbumImage, AlbumImageDao, DbAlbumImage},
+    domain::image_metadata::{ImageMetadata, ImageMetadataDao},
     graphql::{node::Node, Error, Identifiable},
 };
 
-use super::{DbCreateImage, DbImage, DbUpdateImage, ImageDao};
+use super::db_model::INTEGRITY_UNVERIFIED;
+use super::{
+    DbCreateImage, DbImage, DbUpdateImage, ImageDao, INTEGRITY_CORRUPT, INTEGRITY_MISSING,
+    INTEGRITY_OK, MEDIA_IMAGE, MEDIA_VIDEO,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MediaType {
+    Image,
+    Video,
+}
+
+impl From<&str> for MediaType {
+    fn from(media_type: &str) -> Self {
+        match media_type {
+            MEDIA_VIDEO => MediaType::Video,
+            _ => MediaType::Image,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum IntegrityStatus {
+    /// Not scrubbed yet.
+    Unverified,
+    Ok,
+    /// The store serves different bytes than it was given.
+    Corrupt,
+    Missing,
+}
+
+impl From<&str> for IntegrityStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            INTEGRITY_OK => IntegrityStatus::Ok,
+            INTEGRITY_CORRUPT => IntegrityStatus::Corrupt,
+            INTEGRITY_MISSING => IntegrityStatus::Missing,
+            _ => IntegrityStatus::Unverified,
+        }
+    }
+}
+
+impl IntegrityStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntegrityStatus::Unverified => INTEGRITY_UNVERIFIED,
+            IntegrityStatus::Ok => INTEGRITY_OK,
+            IntegrityStatus::Corrupt => INTEGRITY_CORRUPT,
+            IntegrityStatus::Missing => INTEGRITY_MISSING,
+        }
+    }
+}
 
 #[derive(SimpleObject, RelayNodeObject, Debug, Clone)]
 #[graphql(complex)]
@@ -26,6 +78,20 @@ pub struct Image {
     pub is_uploaded: bool,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub content_hash: Option<String>,
+    /// Whether the untouched original was retained and can be fetched from `/api/original/:id`.
+    pub has_original: bool,
+    pub media_type: MediaType,
+    /// The other half of a Live Photo.
+    pub paired_image_id: Option<Uuid>,
+    /// Format of the camera RAW this image was developed from, e.g. `dng`.
+    pub raw_format: Option<String>,
+    /// Whether the RAW original can be downloaded from `/api/original/:id`.
+    pub has_raw_original: bool,
+    /// Storage volume the original was imported from.
+    pub volume_id: Option<Uuid>,
+    pub integrity_status: IntegrityStatus,
+    pub integrity_checked_at: Option<chrono::NaiveDateTime>,
 }
 
 #[ComplexObject]
@@ -63,6 +129,33 @@ impl Image {
             .map_err(|e| -> Error { e.into() })?;
         Ok(albums)
     }
+
+    async fn paired_image(&self, ctx: &Context<'_>) -> Result<Option<Image>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let image = match self.paired_image_id {
+            Some(image_id) => {
+                let img = ImageDao::get_by_id(mm, &image_id).map_err(|e| -> Error { e.into() })?;
+                Some(img.into())
+            }
+            None => None,
+        };
+        Ok(image)
+    }
+
+    async fn exif(&self, ctx: &Context<'_>) -> Result<Option<ImageMetadata>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let metadata = ImageMetadataDao::get_by_image_id(mm, &self.id.to_uuid())
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(metadata.map(|metadata| metadata.into()))
+    }
 }
 
 impl Identifiable for Image {
@@ -104,6 +197,15 @@ impl From<DbImage> for Image {
             is_uploaded: image.is_uploaded,
             created_at: image.created_at,
             updated_at: image.updated_at,
+            content_hash: image.content_hash,
+            has_original: image.archive_path.is_some(),
+            has_raw_original: image.raw_format.is_some() && image.archive_path.is_some(),
+            media_type: image.media_type.as_str().into(),
+            paired_image_id: image.paired_image_id,
+            raw_format: image.raw_format,
+            volume_id: image.volume_id,
+            integrity_status: image.integrity_status.as_str().into(),
+            integrity_checked_at: image.integrity_checked_at,
         }
     }
 }
@@ -133,6 +235,14 @@ impl From<CreateImage> for DbCreateImage {
             width: val.width,
             height: val.height,
             is_uploaded: val.is_uploaded,
+            content_hash: None,
+            archive_path: None,
+            media_type: MEDIA_IMAGE.to_string(),
+            raw_format: None,
+            created_at: None,
+            volume_id: None,
+            lust_checksum: None,
+            stored_hash: None,
         }
     }
 }
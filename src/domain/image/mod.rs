@@ -6,8 +6,9 @@ mod subscription;
 
 pub use db_model::{
     CreateImage as DbCreateImage, Image as DbImage, ImageDao, UpdateImage as DbUpdateImage,
+    INTEGRITY_CORRUPT, INTEGRITY_MISSING, INTEGRITY_OK, MEDIA_IMAGE, MEDIA_VIDEO,
 };
-pub use graphql_model::Image;
+pub use graphql_model::{Image, IntegrityStatus, MediaType};
 pub use mutation::ImageMutation;
 pub use query::ImageQuery;
 pub use subscription::ImageSubscription;
@@ -1,20 +1,20 @@
 use async_graphql::*;
 use async_graphql_relay::RelayNodeID;
-use imagesize::blob_size;
-use reqwest::Client;
-use uuid::Uuid;
 
 use crate::{
     config::config,
     db::ModelManager,
-    domain::album::Album,
+    domain::{
+        album::Album,
+        storage_volume::{StorageVolume, StorageVolumeDao},
+    },
     graphql::{AuthGuard, Error},
-    services::lust::Lust,
-    utils::{delete_file, read_file},
+    ingest::{ingest_raw_file, media, DuplicatePolicy, IngestOptions, RetentionPolicy},
+    services::store::Store,
 };
 
 use super::{
-    db_model::{CreateImage as DbCreateImage, Image as DbImage, ImageDao},
+    db_model::{Image as DbImage, ImageDao},
     graphql_model::{CreateImage, UpdateImage},
     Image,
 };
@@ -63,15 +63,15 @@ impl ImageMutation {
             None => return Err(Error::ModalManagerNotInContext.into()),
         };
 
-        let client = ctx.data_opt::<Client>();
-        let client = match client {
-            Some(client) => client,
-            None => return Err(Error::ClientNotInContext.into()),
+        let store = ctx.data_opt::<Store>();
+        let store = match store {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
         };
 
         let image = ImageDao::get_by_id(mm, &id.to_uuid()).map_err(|e| -> Error { e.into() })?;
 
-        Lust::delete_file(client, &config().LUST_BUCKET, &image.path)
+        media::remove_stored(store, &image)
             .await
             .map_err(|_| -> Error { Error::FailedToDeleteFile })?;
 
@@ -88,6 +88,9 @@ impl ImageMutation {
         is_primary_album: bool,
         album_path: String,
         image_path: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+        on_duplicate: Option<DuplicatePolicy>,
+        retention: Option<RetentionPolicy>,
     ) -> Result<Image> {
         let mm = ctx.data_opt::<ModelManager>();
         let mm = match mm {
@@ -95,52 +98,25 @@ impl ImageMutation {
             None => return Err(Error::ModalManagerNotInContext.into()),
         };
 
-        let client = match ctx.data_opt::<Client>() {
-            Some(client) => client,
-            None => return Err(Error::ClientNotInContext.into()),
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
         };
 
-        let image_name = format!("{}/{}", album_path, image_path.clone());
-        let file = read_file(&image_name)
-            .await
-            .map_err(|_| Error::FailedToReadFile)?;
-
-        let image_dimensions = blob_size(&file).map_err(|_| Error::BadImage)?;
-
-        let response = Lust::post_file(client, &config().LUST_BUCKET, file)
-            .await
-            .map_err(|e| Error::GraphQlError(e.into()))?;
-
-        let is_image_uploaded =
-            ImageDao::is_uploaded(mm, &image_name).map_err(|e| -> Error { e.into() })?;
-
-        if is_image_uploaded {
-            return Err(Error::EntityExists.into());
-        }
-
-        let image_res = ImageDao::create_with_album(
-            mm,
-            &album_id.to_uuid(),
-            DbCreateImage {
-                id: Uuid::new_v4(),
-                title: image_path.clone(),
-                description: None,
-                original_full_title: image_name.clone(),
-                path: response.image_id,
-                width: Some(image_dimensions.width as i32),
-                height: Some(image_dimensions.height as i32),
-                is_uploaded: true,
-            },
-            None,
+        let options = IngestOptions {
+            album_id: album_id.to_uuid(),
             is_primary_album,
-        )
-        .map(|image: DbImage| -> Image { image.into() })
-        .map_err(|e| -> Error { e.into() })?;
+            on_duplicate: on_duplicate.unwrap_or_default(),
+            retention: retention.unwrap_or(config().ORIGINALS_RETENTION),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
 
-        delete_file(&image_name)
-            .await
-            .map_err(|_| Error::FailedToDeleteFile)?;
+        let ingested =
+            ingest_raw_file(mm, store, &options, &volume, &album_path, &image_path, None)
+                .await
+                .map_err(|e| -> Error { e.into() })?;
 
-        Ok(image_res)
+        Ok(ingested.image.into())
     }
 }
@@ -1,19 +1,19 @@
 use async_graphql::Result;
 use async_graphql::*;
-use futures_util::stream::Stream;
-use imagesize::{blob_size, ImageSize};
-use reqwest::Client;
+use async_graphql_relay::RelayNodeID;
+use futures_util::stream::{Stream, StreamExt};
 use serde::Serialize;
-use tracing::error;
 use uuid::Uuid;
 
 use crate::config::config;
 use crate::db::ModelManager;
-use crate::services::lust::{Lust, LustResponse};
-use crate::utils::{delete_file, read_file};
+use crate::domain::storage_volume::{StorageVolume, StorageVolumeDao};
+use crate::ingest::{
+    ingest_raw_file, is_sidecar, media, DuplicatePolicy, IngestOptions, RetentionPolicy,
+};
+use crate::services::store::Store;
 
-use super::Image;
-use super::{DbCreateImage, ImageDao};
+use super::{Image, ImageDao};
 use crate::graphql::{AuthGuard, Error};
 
 #[derive(Default)]
@@ -34,96 +34,66 @@ impl ImageSubscription {
         images: Vec<String>,
         album_id: Uuid,
         album_path: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
         is_primary_album: bool,
+        on_duplicate: Option<DuplicatePolicy>,
+        retention: Option<RetentionPolicy>,
     ) -> Result<impl Stream<Item = Result<Option<Image>>> + 'a> {
-        let client = ctx.data_opt::<Client>();
-        let client = match client {
-            Some(client) => client,
-            None => return Err(Error::ClientNotInContext.into()),
+        let store = ctx.data_opt::<Store>();
+        let store = match store {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
         };
         let mm = ctx.data_opt::<ModelManager>();
         let mm = match mm {
             Some(mm) => mm,
             None => return Err(Error::ModalManagerNotInContext.into()),
         };
-        let mut images = images;
+        let options = IngestOptions {
+            album_id,
+            is_primary_album,
+            on_duplicate: on_duplicate.unwrap_or_default(),
+            retention: retention.unwrap_or(config().ORIGINALS_RETENTION),
+        };
+        let volume =
+            StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid())).map_err(Error::from)?;
+        // Takeout sidecars are read alongside their images, not uploaded
+        let mut images = images
+            .into_iter()
+            .filter(|image| !is_sidecar(image))
+            .collect::<Vec<String>>();
         images.sort();
-        let stream = async_stream::stream! {
-            for image in images {
-                let file = read_file(&format!("{}/{}", &album_path, &image))
+        // Indexes are taken up front from the sorted list, so the album order
+        // doesn't depend on which upload finishes first.
+        let order_offset = ImageDao::get_by_album_id(mm, &album_id)
+            .map(|images| images.len() as i32)
+            .map_err(Error::from)?;
+        let stream = futures_util::stream::iter(images.into_iter().enumerate())
+            .map(move |(i, image)| {
+                let album_path = album_path.clone();
+                let volume = volume.clone();
+                async move {
+                    let order_index = Some(order_offset + i as i32);
+                    ingest_raw_file(
+                        mm,
+                        store,
+                        &options,
+                        &volume,
+                        &album_path,
+                        &image,
+                        order_index,
+                    )
                     .await
-                    .map_err(|_e| -> async_graphql::Error { Error::FailedToReadFile.into() });
-                let ( res, dimensions, size ): ( Result<LustResponse>, Option<ImageSize>, Option<usize> ) = match file {
-                    Ok(file) => {
-                        let image_dimensions = blob_size(&file).map_err(|_| Error::BadImage);
-                        let size = file.len();
-                        let res = Lust::post_file(&client, &config().LUST_BUCKET, file).await.map_err(|e| -> async_graphql::Error { e.into()});
-
-                        (res, image_dimensions.ok(), Some(size))
-                    },
-                    Err(e) => {
-                        (Err(e), None, None)
-                    }
-                };
-                match ( res, dimensions, size ) {
-                    (Ok(res), Some(dimensions), Some(_)) => {
-
-                        let full_path = format!("{}/{}", album_path, image);
-
-                        let is_image_uploaded = ImageDao::is_uploaded(mm, &full_path)
-                            .map_err(|e| -> Error { e.into() })?;
-
-                        match is_image_uploaded {
-                            true => {
-                                yield Err(Error::EntityExists.into());
-                                continue;
-                            }
-                            false => {
-                                let image_res = ImageDao::create_with_album(
-                                    mm,
-                                    &album_id,
-                                    DbCreateImage {
-                                        id: Uuid::new_v4(),
-                                        title: image.clone(),
-                                        description: None,
-                                        original_full_title: full_path.clone(),
-                                        path: res.image_id,
-                                        width: Some(dimensions.width as i32),
-                                        height: Some(dimensions.height as i32),
-                                        is_uploaded: true,
-                                    },
-                                    None,
-                                    is_primary_album,
-                                ).map_err(|e| -> Error { e.into() });
-                                // TODO: delete original image destination
-                                match image_res {
-                                    Ok(image_res) => {
-                                        let image: Image = image_res.into();
-                                        let delete_result = delete_file(&full_path).await;
-                                        match delete_result {
-                                            Ok(_) => () ,
-                                            Err(e) => {
-                                            error!("Failed to delete original file: {:?}", e);
-                                            }
-                                        }
-                                        yield Ok(Some(image));
-                                    }
-                                    Err(e)=> {
-                                        yield Err(e.into());
-                                    }
-                                }
-                            },
-                        }
-                    }
-                    (Err(e), _, _) => {
-                        yield Err(e);
-                    }
-                    (_, _, _) => {
-                        yield Err(Error::BadImage.into());
-                    }
                 }
-            }
-        };
+            })
+            .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1))
+            .map(|ingested| match ingested {
+                Ok(ingested) => {
+                    let image: Image = ingested.image.into();
+                    Ok(Some(image))
+                }
+                Err(e) => Err(Error::from(e).into()),
+            });
         Ok(stream)
     }
 
@@ -133,10 +103,10 @@ impl ImageSubscription {
         ctx: &'a Context<'a>,
         album_id: Uuid,
     ) -> Result<impl Stream<Item = Result<DeletionResult>> + 'a> {
-        let client = ctx.data_opt::<Client>();
-        let client = match client {
-            Some(client) => client,
-            None => return Err(Error::ClientNotInContext.into()),
+        let store = ctx.data_opt::<Store>();
+        let store = match store {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
         };
         let mm = ctx.data_opt::<ModelManager>();
         let mm = match mm {
@@ -150,7 +120,7 @@ impl ImageSubscription {
             for image in images {
                 let image_id = image.id;
                 let image_path = image.path.clone();
-                let delete_result = Lust::delete_file(&client, &config().LUST_BUCKET, &image_path).await;
+                let delete_result = media::remove_stored(store, &image).await;
 
                 match delete_result {
                     Ok(_) => {
@@ -182,10 +152,10 @@ impl ImageSubscription {
         ctx: &'a Context<'a>,
         images: Vec<Uuid>,
     ) -> Result<impl Stream<Item = Result<DeletionResult>> + 'a> {
-        let client = ctx.data_opt::<Client>();
-        let client = match client {
-            Some(client) => client,
-            None => return Err(Error::ClientNotInContext.into()),
+        let store = ctx.data_opt::<Store>();
+        let store = match store {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
         };
         let mm = ctx.data_opt::<ModelManager>();
         let mm = match mm {
@@ -199,7 +169,7 @@ impl ImageSubscription {
             for image in images {
                 let image_id = image.id;
                 let image_path = image.path.clone();
-                let delete_result = Lust::delete_file(&client, &config().LUST_BUCKET, &image_path).await;
+                let delete_result = media::remove_stored(store, &image).await;
 
                 match delete_result {
                     Ok(_) => {
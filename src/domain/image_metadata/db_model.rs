@@ -0,0 +1,75 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{ModelManager, Result};
+use crate::schema::image_metadata;
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = image_metadata)]
+pub struct ImageMetadata {
+    pub image_id: Uuid,
+    pub taken_at: Option<chrono::NaiveDateTime>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f64>,
+    pub orientation: Option<i32>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+    pub gps_altitude: Option<f64>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(AsChangeset, Insertable, Serialize, Debug, Default)]
+#[diesel(table_name = image_metadata)]
+#[diesel(primary_key(image_id))]
+pub struct CreateImageMetadata {
+    pub image_id: Uuid,
+    pub taken_at: Option<chrono::NaiveDateTime>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f64>,
+    pub orientation: Option<i32>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+    pub gps_altitude: Option<f64>,
+}
+
+pub struct ImageMetadataDao;
+
+impl ImageMetadataDao {
+    /// Inserts the metadata, or merges the non-empty fields into an existing row.
+    pub fn upsert(mm: &ModelManager, metadata: &CreateImageMetadata) -> Result<ImageMetadata> {
+        let mut conn = mm.conn()?;
+
+        diesel::insert_into(image_metadata::dsl::image_metadata)
+            .values(metadata)
+            .on_conflict(image_metadata::dsl::image_id)
+            .do_update()
+            .set((
+                metadata,
+                image_metadata::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<ImageMetadata>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn get_by_image_id(mm: &ModelManager, image_id: &Uuid) -> Result<Option<ImageMetadata>> {
+        let mut conn = mm.conn()?;
+
+        image_metadata::dsl::image_metadata
+            .find(image_id)
+            .first::<ImageMetadata>(&mut conn)
+            .optional()
+            .map_err(Into::into)
+    }
+}
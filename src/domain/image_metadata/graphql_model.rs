@@ -0,0 +1,38 @@
+use async_graphql::SimpleObject;
+
+use super::DbImageMetadata;
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct ImageMetadata {
+    pub taken_at: Option<chrono::NaiveDateTime>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f64>,
+    pub orientation: Option<i32>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+    pub gps_altitude: Option<f64>,
+}
+
+impl From<DbImageMetadata> for ImageMetadata {
+    fn from(metadata: DbImageMetadata) -> Self {
+        Self {
+            taken_at: metadata.taken_at,
+            camera_make: metadata.camera_make,
+            camera_model: metadata.camera_model,
+            lens_model: metadata.lens_model,
+            exposure_time: metadata.exposure_time,
+            f_number: metadata.f_number,
+            iso: metadata.iso,
+            focal_length: metadata.focal_length,
+            orientation: metadata.orientation,
+            gps_latitude: metadata.gps_latitude,
+            gps_longitude: metadata.gps_longitude,
+            gps_altitude: metadata.gps_altitude,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+mod db_model;
+mod graphql_model;
+
+pub use db_model::{
+    CreateImageMetadata as DbCreateImageMetadata, ImageMetadata as DbImageMetadata,
+    ImageMetadataDao,
+};
+pub use graphql_model::ImageMetadata;
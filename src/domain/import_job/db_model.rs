@@ -0,0 +1,276 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{Error, ModelManager, Result};
+use crate::schema::{album_image, import_job, import_job_item};
+
+pub const JOB_QUEUED: &str = "queued";
+pub const JOB_RUNNING: &str = "running";
+pub const JOB_COMPLETED: &str = "completed";
+/// Stopped by an error, it is left alone on the next start.
+pub const JOB_FAILED: &str = "failed";
+
+pub const ITEM_PENDING: &str = "pending";
+pub const ITEM_IN_PROGRESS: &str = "in_progress";
+pub const ITEM_DONE: &str = "done";
+pub const ITEM_FAILED: &str = "failed";
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = import_job)]
+pub struct ImportJob {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub album_path: String,
+    pub is_primary_album: bool,
+    pub link_duplicates: bool,
+    pub status: String,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub failed_items: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub order_offset: i32,
+    pub retention: String,
+    pub volume_id: Option<Uuid>,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = import_job)]
+pub struct CreateImportJob {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub album_path: String,
+    pub is_primary_album: bool,
+    pub link_duplicates: bool,
+    pub retention: String,
+    /// The default volume when not set.
+    pub volume_id: Option<Uuid>,
+}
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = import_job_item)]
+pub struct ImportJobItem {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub file_name: String,
+    pub order_index: i32,
+    pub status: String,
+    pub error: Option<String>,
+    pub image_id: Option<Uuid>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = import_job_item)]
+pub struct CreateImportJobItem {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub file_name: String,
+    pub order_index: i32,
+}
+
+pub struct ImportJobDao;
+
+impl ImportJobDao {
+    /// Item order indexes are relative to `order_offset`, the album size when the job was
+    /// created, so images keep their sorted position even when items finish out of order.
+    pub fn create_with_items(
+        mm: &ModelManager,
+        new_job: CreateImportJob,
+        file_names: Vec<String>,
+    ) -> Result<ImportJob> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let items = file_names
+                .into_iter()
+                .enumerate()
+                .map(|(i, file_name)| CreateImportJobItem {
+                    id: Uuid::new_v4(),
+                    job_id: new_job.id,
+                    file_name,
+                    order_index: i as i32,
+                })
+                .collect::<Vec<CreateImportJobItem>>();
+
+            diesel::insert_into(import_job::dsl::import_job)
+                .values(&new_job)
+                .execute(conn)?;
+
+            diesel::insert_into(import_job_item::dsl::import_job_item)
+                .values(&items)
+                .execute(conn)?;
+
+            let order_offset = album_image::dsl::album_image
+                .filter(album_image::dsl::album_id.eq(new_job.album_id))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            diesel::update(import_job::dsl::import_job.find(new_job.id))
+                .set((
+                    import_job::dsl::total_items.eq(items.len() as i32),
+                    import_job::dsl::order_offset.eq(order_offset as i32),
+                ))
+                .get_result::<ImportJob>(conn)
+                .map_err(|e| -> Error { e.into() })
+        })
+    }
+
+    pub fn get_by_id(mm: &ModelManager, id: &Uuid) -> Result<ImportJob> {
+        let mut conn = mm.conn()?;
+
+        import_job::dsl::import_job
+            .find(id)
+            .first::<ImportJob>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list(mm: &ModelManager) -> Result<Vec<ImportJob>> {
+        let mut conn = mm.conn()?;
+
+        import_job::dsl::import_job
+            .order(import_job::dsl::created_at.desc())
+            .load::<ImportJob>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Jobs that were queued or running when the server stopped.
+    pub fn list_unfinished(mm: &ModelManager) -> Result<Vec<ImportJob>> {
+        let mut conn = mm.conn()?;
+
+        import_job::dsl::import_job
+            .filter(import_job::dsl::status.eq_any(vec![JOB_QUEUED, JOB_RUNNING]))
+            .order(import_job::dsl::created_at.asc())
+            .load::<ImportJob>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn set_status(mm: &ModelManager, id: &Uuid, status: &str) -> Result<ImportJob> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(import_job::dsl::import_job.find(id))
+            .set((
+                import_job::dsl::status.eq(status),
+                import_job::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<ImportJob>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Recounts the job progress from its items, so it stays right across restarts.
+    pub fn refresh_progress(mm: &ModelManager, id: &Uuid) -> Result<ImportJob> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let processed = import_job_item::dsl::import_job_item
+                .filter(import_job_item::dsl::job_id.eq(id))
+                .filter(import_job_item::dsl::status.eq_any(vec![ITEM_DONE, ITEM_FAILED]))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            let failed = import_job_item::dsl::import_job_item
+                .filter(import_job_item::dsl::job_id.eq(id))
+                .filter(import_job_item::dsl::status.eq(ITEM_FAILED))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            diesel::update(import_job::dsl::import_job.find(id))
+                .set((
+                    import_job::dsl::processed_items.eq(processed as i32),
+                    import_job::dsl::failed_items.eq(failed as i32),
+                    import_job::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .get_result::<ImportJob>(conn)
+                .map_err(|e| -> Error { e.into() })
+        })
+    }
+
+    pub fn get_items(mm: &ModelManager, job_id: &Uuid) -> Result<Vec<ImportJobItem>> {
+        let mut conn = mm.conn()?;
+
+        import_job_item::dsl::import_job_item
+            .filter(import_job_item::dsl::job_id.eq(job_id))
+            .order(import_job_item::dsl::order_index.asc())
+            .load::<ImportJobItem>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Claims the next pending item of the job by marking it as in progress.
+    pub fn claim_next_item(mm: &ModelManager, job_id: &Uuid) -> Result<Option<ImportJobItem>> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let item = import_job_item::dsl::import_job_item
+                .filter(import_job_item::dsl::job_id.eq(job_id))
+                .filter(import_job_item::dsl::status.eq(ITEM_PENDING))
+                .order(import_job_item::dsl::order_index.asc())
+                .for_update()
+                .skip_locked()
+                .first::<ImportJobItem>(conn)
+                .optional()?;
+
+            match item {
+                Some(item) => diesel::update(import_job_item::dsl::import_job_item.find(item.id))
+                    .set((
+                        import_job_item::dsl::status.eq(ITEM_IN_PROGRESS),
+                        import_job_item::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                    ))
+                    .get_result::<ImportJobItem>(conn)
+                    .optional()
+                    .map_err(|e| -> Error { e.into() }),
+                None => Ok(None),
+            }
+        })
+    }
+
+    pub fn finish_item(
+        mm: &ModelManager,
+        id: &Uuid,
+        image_id: Option<Uuid>,
+        error: Option<String>,
+    ) -> Result<ImportJobItem> {
+        let mut conn = mm.conn()?;
+
+        let status = match error {
+            Some(_) => ITEM_FAILED,
+            None => ITEM_DONE,
+        };
+
+        diesel::update(import_job_item::dsl::import_job_item.find(id))
+            .set((
+                import_job_item::dsl::status.eq(status),
+                import_job_item::dsl::error.eq(error),
+                import_job_item::dsl::image_id.eq(image_id),
+                import_job_item::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<ImportJobItem>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Items that are pending or being imported.
+    pub fn count_open_items(mm: &ModelManager, job_id: &Uuid) -> Result<i64> {
+        let mut conn = mm.conn()?;
+
+        import_job_item::dsl::import_job_item
+            .filter(import_job_item::dsl::job_id.eq(job_id))
+            .filter(import_job_item::dsl::status.eq_any(vec![ITEM_PENDING, ITEM_IN_PROGRESS]))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Puts items that were being processed when the server stopped back in the queue.
+    pub fn reset_in_progress_items(mm: &ModelManager, job_id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(
+            import_job_item::dsl::import_job_item
+                .filter(import_job_item::dsl::job_id.eq(job_id))
+                .filter(import_job_item::dsl::status.eq(ITEM_IN_PROGRESS)),
+        )
+        .set(import_job_item::dsl::status.eq(ITEM_PENDING))
+        .execute(&mut conn)
+        .map_err(Into::into)
+    }
+}
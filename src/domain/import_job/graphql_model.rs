@@ -0,0 +1,161 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use async_graphql_relay::{RelayNode, RelayNodeID, RelayNodeObject};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    db::ModelManager,
+    domain::album::{Album, AlbumDao},
+    graphql::{node::Node, Error, Identifiable},
+};
+
+use super::db_model::{
+    ImportJob as DbImportJob, ImportJobDao, ImportJobItem as DbImportJobItem, ITEM_DONE,
+    ITEM_FAILED, ITEM_IN_PROGRESS, JOB_COMPLETED, JOB_FAILED, JOB_RUNNING,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ImportJobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<&str> for ImportJobStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            JOB_RUNNING => ImportJobStatus::Running,
+            JOB_COMPLETED => ImportJobStatus::Completed,
+            JOB_FAILED => ImportJobStatus::Failed,
+            _ => ImportJobStatus::Queued,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ImportJobItemStatus {
+    Pending,
+    InProgress,
+    Done,
+    Failed,
+}
+
+impl From<&str> for ImportJobItemStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            ITEM_IN_PROGRESS => ImportJobItemStatus::InProgress,
+            ITEM_DONE => ImportJobItemStatus::Done,
+            ITEM_FAILED => ImportJobItemStatus::Failed,
+            _ => ImportJobItemStatus::Pending,
+        }
+    }
+}
+
+#[derive(SimpleObject, RelayNodeObject, Debug, Clone)]
+#[graphql(complex)]
+#[relay(node_suffix = "ij")]
+pub struct ImportJob {
+    pub id: RelayNodeID<Self>,
+    pub album_id: Uuid,
+    pub album_path: String,
+    pub volume_id: Option<Uuid>,
+    pub is_primary_album: bool,
+    pub status: ImportJobStatus,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub failed_items: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[ComplexObject]
+impl ImportJob {
+    async fn album(&self, ctx: &Context<'_>) -> Result<Album, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let album = AlbumDao::get_by_id(mm, &self.album_id).map_err(|e| -> Error { e.into() })?;
+        Ok(album.into())
+    }
+
+    async fn items(&self, ctx: &Context<'_>) -> Result<Vec<ImportJobItem>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let items = ImportJobDao::get_items(mm, &self.id.to_uuid())
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(items.into_iter().map(|item| item.into()).collect())
+    }
+}
+
+impl From<DbImportJob> for ImportJob {
+    fn from(job: DbImportJob) -> Self {
+        Self {
+            id: RelayNodeID::new(job.id),
+            album_id: job.album_id,
+            album_path: job.album_path,
+            volume_id: job.volume_id,
+            is_primary_album: job.is_primary_album,
+            status: job.status.as_str().into(),
+            total_items: job.total_items,
+            processed_items: job.processed_items,
+            failed_items: job.failed_items,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
+
+impl Identifiable for ImportJob {
+    fn get_id(&self) -> Uuid {
+        self.id.to_uuid()
+    }
+}
+
+#[async_trait]
+impl RelayNode for ImportJob {
+    type TNode = Node;
+
+    async fn get(
+        ctx: async_graphql_relay::RelayContext,
+        id: RelayNodeID<Self>,
+    ) -> async_graphql::Result<Option<Self::TNode>> {
+        let mm = ctx.get::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let job = ImportJobDao::get_by_id(mm, &id.to_uuid())
+            .map(|job: DbImportJob| -> ImportJob { job.into() })
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(Some(job.into()))
+    }
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct ImportJobItem {
+    pub file_name: String,
+    pub order_index: i32,
+    pub status: ImportJobItemStatus,
+    pub error: Option<String>,
+    pub image_id: Option<Uuid>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<DbImportJobItem> for ImportJobItem {
+    fn from(item: DbImportJobItem) -> Self {
+        Self {
+            file_name: item.file_name,
+            order_index: item.order_index,
+            status: item.status.as_str().into(),
+            error: item.error,
+            image_id: item.image_id,
+            updated_at: item.updated_at,
+        }
+    }
+}
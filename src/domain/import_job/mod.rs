@@ -0,0 +1,13 @@
+mod db_model;
+mod graphql_model;
+mod mutation;
+mod query;
+mod subscription;
+
+pub use db_model::{
+    CreateImportJob, ImportJob as DbImportJob, ImportJobDao, JOB_COMPLETED, JOB_FAILED, JOB_RUNNING,
+};
+pub use graphql_model::{ImportJob, ImportJobStatus};
+pub use mutation::ImportJobMutation;
+pub use query::ImportJobQuery;
+pub use subscription::ImportJobSubscription;
@@ -0,0 +1,82 @@
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+use uuid::Uuid;
+
+use crate::{
+    config::config,
+    db::ModelManager,
+    domain::{
+        album::Album,
+        raw_album::list_raw_files,
+        storage_volume::{StorageVolume, StorageVolumeDao},
+    },
+    graphql::{AuthGuard, Error},
+    ingest::{DuplicatePolicy, RetentionPolicy},
+    jobs::ImportQueue,
+};
+
+use super::{
+    db_model::{CreateImportJob, ImportJob as DbImportJob},
+    ImportJob,
+};
+
+#[derive(Default)]
+pub struct ImportJobMutation;
+
+#[Object]
+impl ImportJobMutation {
+    #[graphql(guard = "AuthGuard")]
+    async fn create_import_job(
+        &self,
+        ctx: &Context<'_>,
+        album_id: RelayNodeID<Album>,
+        album_path: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+        images: Option<Vec<String>>,
+        is_primary_album: bool,
+        on_duplicate: Option<DuplicatePolicy>,
+        retention: Option<RetentionPolicy>,
+    ) -> Result<ImportJob> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let queue = match ctx.data_opt::<ImportQueue>() {
+            Some(queue) => queue,
+            None => return Err(Error::ImportQueueNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        let images = match images {
+            Some(mut images) => {
+                images.sort();
+                images
+            }
+            None => list_raw_files(&volume.mount_path, &album_path).await?,
+        };
+
+        let job = queue
+            .create_job(
+                mm,
+                CreateImportJob {
+                    id: Uuid::new_v4(),
+                    album_id: album_id.to_uuid(),
+                    album_path,
+                    is_primary_album,
+                    link_duplicates: on_duplicate == Some(DuplicatePolicy::Link),
+                    retention: retention
+                        .unwrap_or(config().ORIGINALS_RETENTION)
+                        .as_str()
+                        .to_string(),
+                    volume_id: Some(volume.id),
+                },
+                images,
+            )
+            .map(|job: DbImportJob| -> ImportJob { job.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(job)
+    }
+}
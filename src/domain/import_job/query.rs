@@ -0,0 +1,57 @@
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+
+use crate::graphql::{uuidIdentifiedQuery, AuthGuard, ConnectionResult, CursorParams};
+use crate::{db::ModelManager, graphql::Error};
+
+use super::{db_model::ImportJobDao, DbImportJob, ImportJob};
+
+#[derive(Default)]
+pub struct ImportJobQuery;
+
+#[Object]
+impl ImportJobQuery {
+    #[graphql(guard = "AuthGuard")]
+    async fn import_job(&self, ctx: &Context<'_>, id: RelayNodeID<ImportJob>) -> Result<ImportJob> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let job = ImportJobDao::get_by_id(mm, &id.to_uuid())
+            .map(|job: DbImportJob| -> ImportJob { job.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(job)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn import_jobs(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> ConnectionResult<ImportJob> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let jobs = ImportJobDao::list(mm)
+            .map(|jobs: Vec<DbImportJob>| -> Vec<ImportJob> {
+                jobs.into_iter().map(|job| job.into()).collect()
+            })
+            .map_err(|e| -> Error { e.into() })?;
+
+        uuidIdentifiedQuery(
+            jobs.into_iter(),
+            CursorParams::new(after, before, first, last),
+            10,
+        )
+        .await
+    }
+}
@@ -0,0 +1,69 @@
+use async_graphql::Result;
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+use futures_util::stream::Stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::db::ModelManager;
+use crate::graphql::{AuthGuard, Error};
+use crate::jobs::ImportQueue;
+
+use super::{ImportJob, ImportJobDao, ImportJobStatus};
+
+#[derive(Default)]
+pub struct ImportJobSubscription;
+
+#[Subscription]
+impl ImportJobSubscription {
+    #[graphql(guard = "AuthGuard")]
+    async fn import_job_progress<'a>(
+        &'a self,
+        ctx: &'a Context<'a>,
+        id: RelayNodeID<ImportJob>,
+    ) -> Result<impl Stream<Item = Result<ImportJob>> + 'a> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let queue = match ctx.data_opt::<ImportQueue>() {
+            Some(queue) => queue,
+            None => return Err(Error::ImportQueueNotInContext.into()),
+        };
+
+        let job_id = id.to_uuid();
+        let mut receiver = queue.subscribe();
+
+        let stream = async_stream::stream! {
+            let mut check = true;
+            loop {
+                if check {
+                    match ImportJobDao::get_by_id(mm, &job_id) {
+                        Ok(job) => {
+                            let job: ImportJob = job.into();
+                            let is_finished = matches!(
+                                job.status,
+                                ImportJobStatus::Completed | ImportJobStatus::Failed
+                            );
+                            yield Ok(job);
+                            if is_finished {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            yield Err(Error::from(e).into());
+                            break;
+                        }
+                    }
+                }
+
+                check = match receiver.recv().await {
+                    Ok(updated_id) => updated_id == job_id,
+                    Err(RecvError::Lagged(_)) => true,
+                    Err(RecvError::Closed) => break,
+                };
+            }
+        };
+        Ok(stream)
+    }
+}
@@ -0,0 +1,70 @@
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::db::{ModelManager, Result};
+use crate::domain::image::DbImage;
+use crate::schema::image;
+
+pub struct IntegrityDao;
+
+impl IntegrityDao {
+    /// Images that were never scrubbed come first, then the ones checked longest ago.
+    pub fn list_for_scrub(mm: &ModelManager, limit: i64) -> Result<Vec<DbImage>> {
+        let mut conn = mm.conn()?;
+
+        image::dsl::image
+            .order(image::dsl::integrity_checked_at.asc().nulls_first())
+            .limit(limit)
+            .load::<DbImage>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list_by_status(mm: &ModelManager, status: &str) -> Result<Vec<DbImage>> {
+        let mut conn = mm.conn()?;
+
+        image::dsl::image
+            .filter(image::dsl::integrity_status.eq(status))
+            .order(image::dsl::integrity_checked_at.desc())
+            .load::<DbImage>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Records a check that couldn't tell anything, the status stays as it was.
+    pub fn set_checked_at(mm: &ModelManager, id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(image::dsl::image.find(id))
+            .set(image::dsl::integrity_checked_at.eq(chrono::Utc::now().naive_utc()))
+            .execute(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// `stored_hash` is only written when one is given, so a known hash is never lost.
+    pub fn set_status(
+        mm: &ModelManager,
+        id: &Uuid,
+        status: &str,
+        stored_hash: Option<String>,
+    ) -> Result<usize> {
+        let mut conn = mm.conn()?;
+        let target = image::dsl::image.find(id);
+        let checked_at = chrono::Utc::now().naive_utc();
+
+        match stored_hash {
+            Some(stored_hash) => diesel::update(target)
+                .set((
+                    image::dsl::integrity_status.eq(status),
+                    image::dsl::integrity_checked_at.eq(checked_at),
+                    image::dsl::stored_hash.eq(stored_hash),
+                ))
+                .execute(&mut conn),
+            None => diesel::update(target)
+                .set((
+                    image::dsl::integrity_status.eq(status),
+                    image::dsl::integrity_checked_at.eq(checked_at),
+                ))
+                .execute(&mut conn),
+        }
+        .map_err(Into::into)
+    }
+}
@@ -0,0 +1,15 @@
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+#[derive(SimpleObject, Default, Debug)]
+pub struct ScrubReport {
+    pub checked: i32,
+    pub ok: i32,
+    /// Images whose stored object no longer matches its hash.
+    pub corrupt: Vec<Uuid>,
+    /// Images whose stored object is gone.
+    pub missing: Vec<Uuid>,
+    /// Images that couldn't be checked, e.g. because the store was unreachable.
+    /// They keep their previous status.
+    pub failed: i32,
+}
@@ -0,0 +1,11 @@
+mod db_model;
+mod graphql_model;
+mod mutation;
+mod query;
+mod scrub;
+
+pub use db_model::IntegrityDao;
+pub use graphql_model::ScrubReport;
+pub use mutation::IntegrityMutation;
+pub use query::IntegrityQuery;
+pub use scrub::scrub_images;
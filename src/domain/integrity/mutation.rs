@@ -0,0 +1,36 @@
+use async_graphql::*;
+
+use crate::{
+    config::config,
+    db::ModelManager,
+    graphql::{AdminGuard, Error},
+    services::store::Store,
+};
+
+use super::{scrub_images, ScrubReport};
+
+#[derive(Default)]
+pub struct IntegrityMutation;
+
+#[Object]
+impl IntegrityMutation {
+    /// Runs a scrub right away instead of waiting for the scheduled one.
+    #[graphql(guard = "AdminGuard")]
+    async fn scrub_images(&self, ctx: &Context<'_>, limit: Option<i64>) -> Result<ScrubReport> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
+        };
+
+        let report = scrub_images(mm, store, limit.unwrap_or(config().SCRUB_BATCH_SIZE))
+            .await
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(report)
+    }
+}
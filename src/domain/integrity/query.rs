@@ -0,0 +1,44 @@
+use async_graphql::*;
+
+use crate::domain::image::{DbImage, Image, IntegrityStatus};
+use crate::graphql::{uuidIdentifiedQuery, AuthGuard, ConnectionResult, CursorParams};
+use crate::{db::ModelManager, graphql::Error};
+
+use super::IntegrityDao;
+
+#[derive(Default)]
+pub struct IntegrityQuery;
+
+#[Object]
+impl IntegrityQuery {
+    /// Images the last scrub left with the given status, e.g. `CORRUPT`.
+    #[graphql(guard = "AuthGuard")]
+    async fn images_by_integrity(
+        &self,
+        ctx: &Context<'_>,
+        status: IntegrityStatus,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> ConnectionResult<Image> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let images = IntegrityDao::list_by_status(mm, status.as_str())
+            .map(|images: Vec<DbImage>| -> Vec<Image> {
+                images.into_iter().map(|image| image.into()).collect()
+            })
+            .map_err(|e| -> Error { e.into() })?;
+
+        uuidIdentifiedQuery(
+            images.into_iter(),
+            CursorParams::new(after, before, first, last),
+            10,
+        )
+        .await
+    }
+}
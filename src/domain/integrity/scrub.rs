@@ -0,0 +1,111 @@
+use futures_util::{stream, StreamExt};
+use tracing::{error, info};
+
+use crate::config::config;
+use crate::db::{ModelManager, Result};
+use crate::domain::image::{
+    DbImage, INTEGRITY_CORRUPT, INTEGRITY_MISSING, INTEGRITY_OK, MEDIA_VIDEO,
+};
+use crate::ingest::{media::media_path, scan_file};
+use crate::services::store::{fetch_object_hash, ImageStore};
+
+use super::{IntegrityDao, ScrubReport};
+
+enum Check {
+    /// Carries the hash to record for objects that had none yet.
+    Intact(Option<String>),
+    Corrupt,
+    Missing,
+}
+
+/// Re-reads up to `limit` stored objects, least recently checked first,
+/// and records whether they still match the hash they were stored with.
+pub async fn scrub_images(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    limit: i64,
+) -> Result<ScrubReport> {
+    let images = IntegrityDao::list_for_scrub(mm, limit)?;
+
+    let checks = stream::iter(images)
+        .map(|image| async move {
+            let check = check_image(store, &image).await;
+            (image, check)
+        })
+        .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut report = ScrubReport::default();
+    for (image, check) in checks {
+        report.checked += 1;
+        let (status, stored_hash) = match check {
+            Ok(Check::Intact(stored_hash)) => {
+                report.ok += 1;
+                (INTEGRITY_OK, stored_hash)
+            }
+            Ok(Check::Corrupt) => {
+                report.corrupt.push(image.id);
+                (INTEGRITY_CORRUPT, None)
+            }
+            Ok(Check::Missing) => {
+                report.missing.push(image.id);
+                (INTEGRITY_MISSING, None)
+            }
+            Err(e) => {
+                error!("{:<12} - failed to check {}: {}", "SCRUB", image.id, e);
+                report.failed += 1;
+                // moves it to the back of the queue, so failing images can't hog every batch
+                IntegrityDao::set_checked_at(mm, &image.id)?;
+                continue;
+            }
+        };
+        IntegrityDao::set_status(mm, &image.id, status, stored_hash)?;
+    }
+
+    info!(
+        "{:<12} - checked: {}, ok: {}, corrupt: {}, missing: {}, failed: {}",
+        "SCRUB",
+        report.checked,
+        report.ok,
+        report.corrupt.len(),
+        report.missing.len(),
+        report.failed
+    );
+
+    Ok(report)
+}
+
+async fn check_image(
+    store: &dyn ImageStore,
+    image: &DbImage,
+) -> std::result::Result<Check, String> {
+    // videos are kept as they were uploaded, so their content hash still applies
+    if image.media_type == MEDIA_VIDEO {
+        let path = media_path(&image.path);
+        if let Err(e) = tokio::fs::metadata(&path).await {
+            return match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(Check::Missing),
+                _ => Err(e.to_string()),
+            };
+        }
+        let file = scan_file(path).await.map_err(|e| e.to_string())?;
+        return match &image.content_hash {
+            Some(hash) if *hash != file.hash => Ok(Check::Corrupt),
+            _ => Ok(Check::Intact(None)),
+        };
+    }
+
+    let hash = match fetch_object_hash(store, &config().LUST_BUCKET, &image.path).await {
+        Ok(hash) => hash,
+        Err(e) if e.is_not_found() => return Ok(Check::Missing),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    Ok(match &image.stored_hash {
+        Some(stored_hash) if *stored_hash == hash => Check::Intact(None),
+        Some(_) => Check::Corrupt,
+        // images ingested before their hash was recorded get it from their first scrub
+        None => Check::Intact(Some(hash)),
+    })
+}
@@ -0,0 +1,71 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{ModelManager, Result};
+use crate::schema::lust_cleanup;
+
+/// A Lust image that nothing references anymore and that couldn't be deleted right away.
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = lust_cleanup)]
+pub struct LustCleanup {
+    pub id: Uuid,
+    pub bucket: String,
+    pub lust_image_id: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = lust_cleanup)]
+pub struct CreateLustCleanup {
+    pub id: Uuid,
+    pub bucket: String,
+    pub lust_image_id: String,
+    pub last_error: Option<String>,
+}
+
+pub struct LustCleanupDao;
+
+impl LustCleanupDao {
+    pub fn create(mm: &ModelManager, new_cleanup: CreateLustCleanup) -> Result<LustCleanup> {
+        let mut conn = mm.conn()?;
+
+        diesel::insert_into(lust_cleanup::dsl::lust_cleanup)
+            .values(&new_cleanup)
+            .get_result::<LustCleanup>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list(mm: &ModelManager) -> Result<Vec<LustCleanup>> {
+        let mut conn = mm.conn()?;
+
+        lust_cleanup::dsl::lust_cleanup
+            .order(lust_cleanup::dsl::created_at.asc())
+            .load::<LustCleanup>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn record_failure(mm: &ModelManager, id: &Uuid, error: String) -> Result<LustCleanup> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(lust_cleanup::dsl::lust_cleanup.find(id))
+            .set((
+                lust_cleanup::dsl::attempts.eq(lust_cleanup::dsl::attempts + 1),
+                lust_cleanup::dsl::last_error.eq(Some(error)),
+                lust_cleanup::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<LustCleanup>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn delete(mm: &ModelManager, id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(lust_cleanup::dsl::lust_cleanup.find(id))
+            .execute(&mut conn)
+            .map_err(Into::into)
+    }
+}
@@ -1,5 +1,13 @@
 pub mod album;
 pub mod album_image_options;
+pub mod consistency;
 pub mod image;
+pub mod image_metadata;
+pub mod integrity;
+pub mod import_job;
+pub mod lust_cleanup;
 pub mod raw_album;
+pub mod storage_migration;
+pub mod storage_volume;
+pub mod upload_session;
 pub mod user;
@@ -0,0 +1,215 @@
+use async_graphql::SimpleObject;
+use futures_util::future::{BoxFuture, FutureExt};
+use futures_util::{stream, StreamExt};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::album::{Album, AlbumDao, DbCreateAlbum, DbUpdateAlbum};
+use crate::domain::storage_volume::DbStorageVolume;
+use crate::graphql::{Error, Result};
+use crate::ingest::{ingest_raw_file, DuplicatePolicy, IngestOptions, RetentionPolicy};
+use crate::services::store::ImageStore;
+
+use super::{list_raw_files, RawAlbumTree};
+
+#[derive(SimpleObject, Debug)]
+pub struct ImportedFile {
+    pub name: String,
+    pub image_id: Option<Uuid>,
+    pub linked: bool,
+    pub error: Option<String>,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct RawAlbumImport {
+    pub album: Album,
+    pub imported: i32,
+    pub linked: i32,
+    pub failed: i32,
+    pub files: Vec<ImportedFile>,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct RawAlbumTreeImport {
+    pub import: RawAlbumImport,
+    pub children: Vec<RawAlbumTreeImport>,
+    /// Subdirectories that couldn't be imported, their own subdirectories were skipped too.
+    pub failed_children: Vec<RawAlbumTreeFailure>,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct RawAlbumTreeFailure {
+    pub path: String,
+    pub error: String,
+}
+
+/// Creates an album for the raw directory at `path` and imports all of its files in name order.
+/// The album is named after the directory and keeps `path` as its original title.
+/// The first imported image becomes the cover and the album is marked as uploaded.
+pub async fn import_raw_album(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    volume: &DbStorageVolume,
+    path: String,
+    parent_id: Option<Uuid>,
+    is_primary_album: bool,
+    on_duplicate: DuplicatePolicy,
+    retention: RetentionPolicy,
+) -> Result<RawAlbumImport> {
+    let title = path.rsplit('/').next().unwrap_or(&path).to_string();
+    let existing = match &parent_id {
+        Some(parent_id) => AlbumDao::get_by_title_and_parent(mm, &title, parent_id)?,
+        None => AlbumDao::get_by_title(mm, &title)?,
+    };
+    if existing.is_some() {
+        return Err(Error::EntityExists);
+    }
+    let file_names = list_raw_files(&volume.mount_path, &path).await?;
+
+    let album = AlbumDao::create(
+        mm,
+        DbCreateAlbum {
+            id: Uuid::new_v4(),
+            title,
+            description: None,
+            original_title: path.clone(),
+            parent_id,
+        },
+    )?;
+    let options = IngestOptions {
+        album_id: album.id,
+        is_primary_album,
+        on_duplicate,
+        retention,
+    };
+
+    let mut files = stream::iter(file_names.into_iter().enumerate())
+        .map(|(i, name)| {
+            let path = &path;
+            let options = &options;
+            async move {
+                let res =
+                    ingest_raw_file(mm, store, options, volume, path, &name, Some(i as i32)).await;
+                (i, name, res)
+            }
+        })
+        .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    files.sort_by_key(|(i, _, _)| *i);
+
+    let cover = files
+        .iter()
+        .find_map(|(_, _, res)| res.as_ref().ok().map(|ingested| ingested.image.id));
+    let files = files
+        .into_iter()
+        .map(|(_, name, res)| match res {
+            Ok(ingested) => ImportedFile {
+                name,
+                image_id: Some(ingested.image.id),
+                linked: ingested.linked,
+                error: None,
+            },
+            Err(e) => ImportedFile {
+                name,
+                image_id: None,
+                linked: false,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect::<Vec<ImportedFile>>();
+
+    let album = AlbumDao::update(
+        mm,
+        &album.id,
+        DbUpdateAlbum {
+            title: None,
+            description: None,
+            original_title: None,
+            is_uploaded: Some(true),
+            prev_image_id: cover,
+        },
+    )?;
+
+    Ok(RawAlbumImport {
+        album: album.into(),
+        imported: files
+            .iter()
+            .filter(|f| f.image_id.is_some() && !f.linked)
+            .count() as i32,
+        linked: files.iter().filter(|f| f.linked).count() as i32,
+        failed: files.iter().filter(|f| f.error.is_some()).count() as i32,
+        files,
+    })
+}
+
+/// Imports a raw directory and every nested subdirectory, each as a child album of its parent.
+/// Only a failing root fails the import, subdirectories that fail are listed with their parent.
+pub fn import_raw_album_tree<'a>(
+    mm: &'a ModelManager,
+    store: &'a dyn ImageStore,
+    volume: &'a DbStorageVolume,
+    tree: RawAlbumTree,
+    parent_id: Option<Uuid>,
+    is_primary_album: bool,
+    on_duplicate: DuplicatePolicy,
+    retention: RetentionPolicy,
+) -> BoxFuture<'a, Result<RawAlbumTreeImport>> {
+    async move {
+        let import = import_raw_album(
+            mm,
+            store,
+            volume,
+            tree.path,
+            parent_id,
+            is_primary_album,
+            on_duplicate,
+            retention,
+        )
+        .await?;
+        let album_id = import.album.id.to_uuid();
+
+        let mut children = Vec::new();
+        let mut failed_children = tree
+            .unreadable
+            .into_iter()
+            .map(|path| RawAlbumTreeFailure {
+                path,
+                error: "directory can't be read".to_string(),
+            })
+            .collect::<Vec<RawAlbumTreeFailure>>();
+        for child in tree.children {
+            let path = child.path.clone();
+            let res = import_raw_album_tree(
+                mm,
+                store,
+                volume,
+                child,
+                Some(album_id),
+                is_primary_album,
+                on_duplicate,
+                retention,
+            )
+            .await;
+            match res {
+                Ok(child) => children.push(child),
+                Err(e) => {
+                    error!("{:<12} - failed to import {}: {}", "RAW_ALBUM", path, e);
+                    failed_children.push(RawAlbumTreeFailure {
+                        path,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(RawAlbumTreeImport {
+            import,
+            children,
+            failed_children,
+        })
+    }
+    .boxed()
+}
@@ -1,15 +1,23 @@
 use std::fs::Metadata;
 
 use async_graphql::SimpleObject;
+use futures_util::future::{BoxFuture, FutureExt};
 use tokio::fs::read_dir;
+use tracing::error;
 
-use crate::config;
 use crate::graphql::{Error, IdentifiableString, Result};
+use crate::ingest::is_sidecar;
 
+mod import;
 mod mutation;
+mod preflight;
 mod query;
 
+pub use import::{
+    import_raw_album, import_raw_album_tree, ImportedFile, RawAlbumImport, RawAlbumTreeImport,
+};
 pub use mutation::RawAlbumMutation;
+pub use preflight::{DuplicateKind, PreflightFile, PreflightReport};
 pub use query::RawAlbumQuery;
 
 #[derive(SimpleObject)]
@@ -28,12 +36,12 @@ fn get_file_created_time(meta: Metadata) -> Result<i64> {
     Ok(time.as_secs() as i64)
 }
 
-async fn read_path(path: Option<String>) -> Result<Vec<DirItem>> {
-    let root = config::config().IMAGES_DIR.clone();
+/// Lists a directory given relative to `root`, the mount point of a storage volume.
+async fn read_path(root: &str, path: Option<String>) -> Result<Vec<DirItem>> {
     let path = if let Some(path) = path {
-        root + "/" + &path
+        format!("{}/{}", root, path)
     } else {
-        root
+        root.to_string()
     };
 
     let mut dirs = read_dir(path).await.map_err(|_| Error::FailedToReadDir)?;
@@ -45,6 +53,10 @@ async fn read_path(path: Option<String>) -> Result<Vec<DirItem>> {
         .await
         .map_err(|_| Error::FailedToReadDir)?
     {
+        // skip hidden entries, e.g. the upload staging directory
+        if dir.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
         let d = dir.metadata().await.map_err(|_| Error::FailedToReadDir)?;
         if d.is_dir() {
             files.push(DirItem {
@@ -70,6 +82,17 @@ async fn read_path(path: Option<String>) -> Result<Vec<DirItem>> {
     Ok(files)
 }
 
+pub async fn list_raw_files(root: &str, path: &str) -> Result<Vec<String>> {
+    let mut files = read_path(root, Some(path.to_string()))
+        .await?
+        .into_iter()
+        .filter(|d| !d.is_dir && !is_sidecar(&d.name))
+        .map(|d| d.name)
+        .collect::<Vec<String>>();
+    files.sort();
+    Ok(files)
+}
+
 #[derive(SimpleObject, Clone, Debug)]
 pub struct RawAlbumString {
     title: String,
@@ -83,8 +106,8 @@ impl IdentifiableString for RawAlbumString {
 }
 
 impl RawAlbumString {
-    pub async fn read(path: Option<String>) -> Result<Vec<Self>> {
-        let dirs = read_path(path)
+    pub async fn read(root: &str, path: Option<String>) -> Result<Vec<Self>> {
+        let dirs = read_path(root, path)
             .await?
             .into_iter()
             .filter(|d| d.is_dir)
@@ -105,9 +128,57 @@ pub struct RawAlbum {
 }
 
 impl RawAlbum {
-    pub async fn read(title: String) -> Result<Self> {
-        let mut dirs = read_path(Some(title.clone())).await?;
+    pub async fn read(root: &str, title: String) -> Result<Self> {
+        let mut dirs = read_path(root, Some(title.clone())).await?;
         dirs.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(Self { title, items: dirs })
     }
 }
+
+/// A raw directory with all of its nested subdirectories.
+#[derive(SimpleObject)]
+pub struct RawAlbumTree {
+    pub title: String,
+    /// Path relative to the mount point of its storage volume.
+    pub path: String,
+    pub files: Vec<String>,
+    pub children: Vec<RawAlbumTree>,
+    /// Paths of subdirectories that couldn't be read.
+    pub unreadable: Vec<String>,
+}
+
+impl RawAlbumTree {
+    pub fn read(root: &str, path: String) -> BoxFuture<'_, Result<Self>> {
+        async move {
+            let mut items = read_path(root, Some(path.clone())).await?;
+            items.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let mut files = Vec::new();
+            let mut children = Vec::new();
+            let mut unreadable = Vec::new();
+            for item in items {
+                if item.is_dir {
+                    let child_path = format!("{}/{}", path, item.name);
+                    match Self::read(root, child_path.clone()).await {
+                        Ok(child) => children.push(child),
+                        Err(e) => {
+                            error!("{:<12} - failed to read {}: {}", "RAW_ALBUM", child_path, e);
+                            unreadable.push(child_path);
+                        }
+                    }
+                } else if !is_sidecar(&item.name) {
+                    files.push(item.name);
+                }
+            }
+
+            Ok(Self {
+                title: path.rsplit('/').next().unwrap_or(&path).to_string(),
+                path,
+                files,
+                children,
+                unreadable,
+            })
+        }
+        .boxed()
+    }
+}
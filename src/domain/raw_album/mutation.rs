@@ -1,6 +1,19 @@
 use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
 
-use crate::{config::config, graphql::AuthGuard, utils::delete_file};
+use crate::{
+    config::config,
+    db::ModelManager,
+    domain::storage_volume::{StorageVolume, StorageVolumeDao},
+    graphql::{AuthGuard, Error},
+    ingest::{DuplicatePolicy, RetentionPolicy},
+    services::store::Store,
+    utils::delete_file,
+};
+
+use super::{
+    import_raw_album, import_raw_album_tree, RawAlbumImport, RawAlbumTree, RawAlbumTreeImport,
+};
 
 #[derive(Default)]
 pub struct RawAlbumMutation;
@@ -16,24 +29,131 @@ impl RawAlbumMutation {
     #[graphql(guard = "AuthGuard")]
     async fn delete_raw_files(
         &self,
-        _ctx: &Context<'_>,
+        ctx: &Context<'_>,
         paths: Vec<String>,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
     ) -> FieldResult<Vec<DeleteResult>> {
-        let root = config().IMAGES_DIR.clone();
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+        if volume.is_read_only {
+            return Err(Error::VolumeReadOnly.into());
+        }
+
         let mut deleted = Vec::new();
         for path in paths {
-            let path = format!("{}/{}", root, path);
-            let res = delete_file(&path).await.is_ok();
-            deleted.push(DeleteResult { path, success: res });
+            let res = delete_file(&volume.mount_path, &path).await.is_ok();
+            deleted.push(DeleteResult {
+                path: volume.path_of(&path),
+                success: res,
+            });
         }
         Ok(deleted)
     }
 
     #[graphql(guard = "AuthGuard")]
-    async fn delete_dir(&self, _ctx: &Context<'_>, path: String) -> FieldResult<DeleteResult> {
-        let root = config().IMAGES_DIR.clone();
-        let path = format!("{}/{}", root, path);
-        let res = delete_file(&path).await.is_ok();
-        Ok(DeleteResult { path, success: res })
+    async fn delete_dir(
+        &self,
+        ctx: &Context<'_>,
+        path: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+    ) -> FieldResult<DeleteResult> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+        if volume.is_read_only {
+            return Err(Error::VolumeReadOnly.into());
+        }
+
+        let res = delete_file(&volume.mount_path, &path).await.is_ok();
+        Ok(DeleteResult {
+            path: volume.path_of(&path),
+            success: res,
+        })
+    }
+
+    /// Creates an album from a raw directory and imports all of its files in one go.
+    #[graphql(guard = "AuthGuard")]
+    async fn import_raw_album(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+        is_primary_album: Option<bool>,
+        on_duplicate: Option<DuplicatePolicy>,
+        retention: Option<RetentionPolicy>,
+    ) -> Result<RawAlbumImport> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        let import = import_raw_album(
+            mm,
+            store,
+            &volume,
+            title,
+            None,
+            is_primary_album.unwrap_or(true),
+            on_duplicate.unwrap_or_default(),
+            retention.unwrap_or(config().ORIGINALS_RETENTION),
+        )
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Like `importRawAlbum`, but nested directories are imported as child albums.
+    #[graphql(guard = "AuthGuard")]
+    async fn import_raw_album_tree(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+        is_primary_album: Option<bool>,
+        on_duplicate: Option<DuplicatePolicy>,
+        retention: Option<RetentionPolicy>,
+    ) -> Result<RawAlbumTreeImport> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        let tree = RawAlbumTree::read(&volume.mount_path, title).await?;
+        let import = import_raw_album_tree(
+            mm,
+            store,
+            &volume,
+            tree,
+            None,
+            is_primary_album.unwrap_or(true),
+            on_duplicate.unwrap_or_default(),
+            retention.unwrap_or(config().ORIGINALS_RETENTION),
+        )
+        .await?;
+
+        Ok(import)
     }
 }
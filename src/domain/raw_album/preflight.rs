@@ -0,0 +1,132 @@
+use async_graphql::{Enum, SimpleObject};
+use imagesize::{blob_size, image_type, size};
+use uuid::Uuid;
+
+use crate::db::ModelManager;
+use crate::domain::image::ImageDao;
+use crate::domain::storage_volume::DbStorageVolume;
+use crate::graphql::Result;
+use crate::ingest::{media, raw, scan_file, ScannedFile};
+
+use super::list_raw_files;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DuplicateKind {
+    /// An image was already imported from the same path.
+    Path,
+    /// An image with the same content already exists.
+    Hash,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct PreflightFile {
+    pub name: String,
+    pub decodable: bool,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub size_bytes: i64,
+    pub format: Option<String>,
+    pub duplicate: Option<DuplicateKind>,
+    pub duplicate_of: Option<Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(SimpleObject, Debug)]
+pub struct PreflightReport {
+    pub title: String,
+    pub files: Vec<PreflightFile>,
+    pub total_files: i32,
+    pub decodable_files: i32,
+    pub duplicate_files: i32,
+    /// Bytes of the files that would actually be sent to Lust,
+    /// i.e. decodable files that are not duplicates.
+    pub total_upload_bytes: i64,
+}
+
+impl PreflightReport {
+    /// Inspects every file of the raw album without uploading or changing anything.
+    pub async fn build(mm: &ModelManager, volume: &DbStorageVolume, title: String) -> Result<Self> {
+        let mut files = Vec::new();
+
+        for name in list_raw_files(&volume.mount_path, &title).await? {
+            let path = format!("{}/{}", title, name);
+            let file = match scan_file(format!("{}/{}", volume.mount_path, path)).await {
+                Ok(file) => inspect_file(mm, name, &path, &file).await?,
+                Err(_) => PreflightFile {
+                    name,
+                    decodable: false,
+                    width: None,
+                    height: None,
+                    size_bytes: 0,
+                    format: None,
+                    duplicate: None,
+                    duplicate_of: None,
+                    error: Some("Failed to read file".to_string()),
+                },
+            };
+            files.push(file);
+        }
+
+        let total_upload_bytes = files
+            .iter()
+            .filter(|f| f.decodable && f.duplicate.is_none())
+            .map(|f| f.size_bytes)
+            .sum();
+
+        Ok(Self {
+            title,
+            total_files: files.len() as i32,
+            decodable_files: files.iter().filter(|f| f.decodable).count() as i32,
+            duplicate_files: files.iter().filter(|f| f.duplicate.is_some()).count() as i32,
+            total_upload_bytes,
+            files,
+        })
+    }
+}
+
+async fn inspect_file(
+    mm: &ModelManager,
+    name: String,
+    path: &str,
+    file: &ScannedFile,
+) -> Result<PreflightFile> {
+    let head = &file.head;
+    let raw_format = raw::detect_raw(&name, head);
+    let dimensions = match raw_format {
+        // ingest needs the embedded preview, the container only has the better dimensions
+        Some(_) => tokio::fs::read(&file.path).await.ok().and_then(|bytes| {
+            raw::extract_preview(&bytes)
+                .and_then(|preview| raw::raw_dimensions(&bytes).or(blob_size(&preview).ok()))
+        }),
+        None => blob_size(head).ok().or_else(|| size(&file.path).ok()),
+    };
+    let video = media::detect_video(head);
+    let format = match (raw_format, video) {
+        (Some(extension), _) | (_, Some(extension)) => Some(extension.to_string()),
+        _ => image_type(head).ok().map(|t| format!("{:?}", t)),
+    };
+
+    let (duplicate, duplicate_of) = if ImageDao::is_uploaded(mm, path)? {
+        (Some(DuplicateKind::Path), None)
+    } else {
+        match ImageDao::get_by_content_hash(mm, &file.hash)? {
+            Some(existing) => (Some(DuplicateKind::Hash), Some(existing.id)),
+            None => (None, None),
+        }
+    };
+
+    Ok(PreflightFile {
+        name,
+        decodable: dimensions.is_some() || video.is_some(),
+        width: dimensions.as_ref().map(|d| d.width as i32),
+        height: dimensions.as_ref().map(|d| d.height as i32),
+        size_bytes: file.size as i64,
+        format,
+        duplicate,
+        duplicate_of,
+        error: match (dimensions, video) {
+            (None, None) => Some("Bad image".to_string()),
+            _ => None,
+        },
+    })
+}
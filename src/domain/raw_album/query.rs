@@ -1,31 +1,100 @@
 use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
 
-use crate::graphql::{stringIdentifiedQuery, AuthGuard, CursorParams, StringConnectionResult};
+use crate::db::ModelManager;
+use crate::domain::storage_volume::{StorageVolume, StorageVolumeDao};
+use crate::graphql::{
+    stringIdentifiedQuery, AuthGuard, CursorParams, Error, StringConnectionResult,
+};
 
-use super::{RawAlbum, RawAlbumString};
+use super::{PreflightReport, RawAlbum, RawAlbumString, RawAlbumTree};
 
 #[derive(Default)]
 pub struct RawAlbumQuery;
 
+/// Raw directories are read from the given storage volume, or from `IMAGES_DIR` without one.
 #[Object]
 impl RawAlbumQuery {
     #[graphql(guard = "AuthGuard")]
     async fn dirs(
         &self,
+        ctx: &Context<'_>,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
         after: Option<String>,
         before: Option<String>,
         first: Option<i32>,
         last: Option<i32>,
     ) -> StringConnectionResult<RawAlbumString> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
         stringIdentifiedQuery(
-            RawAlbumString::read(None).await?.into_iter(),
+            RawAlbumString::read(&volume.mount_path, None)
+                .await?
+                .into_iter(),
             CursorParams::new(after, before, first, last),
             10,
         )
         .await
     }
     #[graphql(guard = "AuthGuard")]
-    async fn dir(&self, title: String) -> Result<RawAlbum> {
-        Ok(RawAlbum::read(title).await?)
+    async fn dir(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+    ) -> Result<RawAlbum> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(RawAlbum::read(&volume.mount_path, title).await?)
+    }
+
+    #[graphql(guard = "AuthGuard")]
+    async fn dir_tree(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+    ) -> Result<RawAlbumTree> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(RawAlbumTree::read(&volume.mount_path, title).await?)
+    }
+
+    /// Dry run of an album import: reports what would happen to each file
+    /// without uploading anything.
+    #[graphql(guard = "AuthGuard")]
+    async fn preflight(
+        &self,
+        ctx: &Context<'_>,
+        title: String,
+        volume_id: Option<RelayNodeID<StorageVolume>>,
+    ) -> Result<PreflightReport> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::resolve(mm, volume_id.map(|id| id.to_uuid()))
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(PreflightReport::build(mm, &volume, title).await?)
     }
 }
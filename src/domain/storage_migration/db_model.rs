@@ -0,0 +1,349 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{Error, ModelManager, Result};
+use crate::domain::image::{INTEGRITY_OK, MEDIA_IMAGE};
+use crate::schema::{image, storage_migration, storage_migration_item};
+
+pub const MIGRATION_QUEUED: &str = "queued";
+pub const MIGRATION_RUNNING: &str = "running";
+pub const MIGRATION_COMPLETED: &str = "completed";
+/// Stopped by an error, it is left alone on the next start.
+pub const MIGRATION_FAILED: &str = "failed";
+
+pub const ITEM_PENDING: &str = "pending";
+pub const ITEM_IN_PROGRESS: &str = "in_progress";
+pub const ITEM_DONE: &str = "done";
+pub const ITEM_FAILED: &str = "failed";
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = storage_migration)]
+pub struct StorageMigration {
+    pub id: Uuid,
+    pub target_backend: String,
+    pub target_bucket: String,
+    pub delete_source: bool,
+    pub status: String,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub failed_items: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = storage_migration)]
+pub struct CreateStorageMigration {
+    pub id: Uuid,
+    pub target_backend: String,
+    pub target_bucket: String,
+    pub delete_source: bool,
+}
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = storage_migration_item)]
+pub struct StorageMigrationItem {
+    pub id: Uuid,
+    pub migration_id: Uuid,
+    pub image_id: Uuid,
+    /// `image.path` when the migration was created.
+    pub source_path: String,
+    /// Set as soon as the copy is stored, so a copy left behind by a restart can be removed.
+    pub target_path: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = storage_migration_item)]
+struct CreateStorageMigrationItem {
+    id: Uuid,
+    migration_id: Uuid,
+    image_id: Uuid,
+    source_path: String,
+}
+
+/// What the image row gets once its copy is verified.
+pub struct MovedImage {
+    pub path: String,
+    pub stored_hash: String,
+    pub lust_checksum: Option<i64>,
+}
+
+pub struct StorageMigrationDao;
+
+impl StorageMigrationDao {
+    /// Ids and paths of every image kept in the image store, videos live in `MEDIA_DIR`.
+    pub fn list_image_paths(mm: &ModelManager) -> Result<Vec<(Uuid, String)>> {
+        let mut conn = mm.conn()?;
+
+        image::dsl::image
+            .filter(image::dsl::media_type.eq(MEDIA_IMAGE))
+            .filter(image::dsl::path.ne(""))
+            .select((image::dsl::id, image::dsl::path))
+            .order(image::dsl::created_at.asc())
+            .load::<(Uuid, String)>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn create_with_items(
+        mm: &ModelManager,
+        new_migration: CreateStorageMigration,
+        images: Vec<(Uuid, String)>,
+    ) -> Result<StorageMigration> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let items = images
+                .into_iter()
+                .map(|(image_id, source_path)| CreateStorageMigrationItem {
+                    id: Uuid::new_v4(),
+                    migration_id: new_migration.id,
+                    image_id,
+                    source_path,
+                })
+                .collect::<Vec<CreateStorageMigrationItem>>();
+
+            diesel::insert_into(storage_migration::dsl::storage_migration)
+                .values(&new_migration)
+                .execute(conn)?;
+
+            // postgres caps the bind parameters of a single statement
+            for chunk in items.chunks(1000) {
+                diesel::insert_into(storage_migration_item::dsl::storage_migration_item)
+                    .values(chunk)
+                    .execute(conn)?;
+            }
+
+            diesel::update(storage_migration::dsl::storage_migration.find(new_migration.id))
+                .set(storage_migration::dsl::total_items.eq(items.len() as i32))
+                .get_result::<StorageMigration>(conn)
+                .map_err(|e| -> Error { e.into() })
+        })
+    }
+
+    pub fn get_by_id(mm: &ModelManager, id: &Uuid) -> Result<StorageMigration> {
+        let mut conn = mm.conn()?;
+
+        storage_migration::dsl::storage_migration
+            .find(id)
+            .first::<StorageMigration>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list(mm: &ModelManager) -> Result<Vec<StorageMigration>> {
+        let mut conn = mm.conn()?;
+
+        storage_migration::dsl::storage_migration
+            .order(storage_migration::dsl::created_at.desc())
+            .load::<StorageMigration>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list_unfinished(mm: &ModelManager) -> Result<Vec<StorageMigration>> {
+        let mut conn = mm.conn()?;
+
+        storage_migration::dsl::storage_migration
+            .filter(
+                storage_migration::dsl::status.eq_any(vec![MIGRATION_QUEUED, MIGRATION_RUNNING]),
+            )
+            .order(storage_migration::dsl::created_at.asc())
+            .load::<StorageMigration>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn set_status(mm: &ModelManager, id: &Uuid, status: &str) -> Result<StorageMigration> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(storage_migration::dsl::storage_migration.find(id))
+            .set((
+                storage_migration::dsl::status.eq(status),
+                storage_migration::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<StorageMigration>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Recounts the progress from the items, so it stays right across restarts.
+    pub fn refresh_progress(mm: &ModelManager, id: &Uuid) -> Result<StorageMigration> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let processed = storage_migration_item::dsl::storage_migration_item
+                .filter(storage_migration_item::dsl::migration_id.eq(id))
+                .filter(storage_migration_item::dsl::status.eq_any(vec![ITEM_DONE, ITEM_FAILED]))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            let failed = storage_migration_item::dsl::storage_migration_item
+                .filter(storage_migration_item::dsl::migration_id.eq(id))
+                .filter(storage_migration_item::dsl::status.eq(ITEM_FAILED))
+                .count()
+                .get_result::<i64>(conn)?;
+
+            diesel::update(storage_migration::dsl::storage_migration.find(id))
+                .set((
+                    storage_migration::dsl::processed_items.eq(processed as i32),
+                    storage_migration::dsl::failed_items.eq(failed as i32),
+                    storage_migration::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .get_result::<StorageMigration>(conn)
+                .map_err(|e| -> Error { e.into() })
+        })
+    }
+
+    pub fn get_failed_items(
+        mm: &ModelManager,
+        migration_id: &Uuid,
+    ) -> Result<Vec<StorageMigrationItem>> {
+        let mut conn = mm.conn()?;
+
+        storage_migration_item::dsl::storage_migration_item
+            .filter(storage_migration_item::dsl::migration_id.eq(migration_id))
+            .filter(storage_migration_item::dsl::status.eq(ITEM_FAILED))
+            .order(storage_migration_item::dsl::updated_at.asc())
+            .load::<StorageMigrationItem>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Claims the next pending item of the migration by marking it as in progress.
+    pub fn claim_next_item(
+        mm: &ModelManager,
+        migration_id: &Uuid,
+    ) -> Result<Option<StorageMigrationItem>> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let item = storage_migration_item::dsl::storage_migration_item
+                .filter(storage_migration_item::dsl::migration_id.eq(migration_id))
+                .filter(storage_migration_item::dsl::status.eq(ITEM_PENDING))
+                .for_update()
+                .skip_locked()
+                .first::<StorageMigrationItem>(conn)
+                .optional()?;
+
+            match item {
+                Some(item) => diesel::update(
+                    storage_migration_item::dsl::storage_migration_item.find(item.id),
+                )
+                .set((
+                    storage_migration_item::dsl::status.eq(ITEM_IN_PROGRESS),
+                    storage_migration_item::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .get_result::<StorageMigrationItem>(conn)
+                .optional()
+                .map_err(|e| -> Error { e.into() }),
+                None => Ok(None),
+            }
+        })
+    }
+
+    pub fn set_target_path(
+        mm: &ModelManager,
+        id: &Uuid,
+        target_path: Option<&str>,
+    ) -> Result<StorageMigrationItem> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(storage_migration_item::dsl::storage_migration_item.find(id))
+            .set((
+                storage_migration_item::dsl::target_path.eq(target_path),
+                storage_migration_item::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<StorageMigrationItem>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Points the image at its copy and finishes the item in one transaction.
+    /// Nothing changes and `false` is returned when the image no longer has the path
+    /// it had when the migration was created.
+    pub fn move_image(
+        mm: &ModelManager,
+        item: &StorageMigrationItem,
+        moved: MovedImage,
+    ) -> Result<bool> {
+        let mut conn = mm.conn()?;
+        let now = chrono::Utc::now().naive_utc();
+
+        conn.transaction(|conn| {
+            let updated = diesel::update(
+                image::dsl::image
+                    .filter(image::dsl::id.eq(item.image_id))
+                    .filter(image::dsl::path.eq(&item.source_path)),
+            )
+            .set((
+                image::dsl::path.eq(&moved.path),
+                image::dsl::stored_hash.eq(&moved.stored_hash),
+                image::dsl::lust_checksum.eq(moved.lust_checksum),
+                image::dsl::integrity_status.eq(INTEGRITY_OK),
+                image::dsl::integrity_checked_at.eq(now),
+                image::dsl::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+            if updated == 0 {
+                return Ok(false);
+            }
+
+            diesel::update(storage_migration_item::dsl::storage_migration_item.find(item.id))
+                .set((
+                    storage_migration_item::dsl::status.eq(ITEM_DONE),
+                    storage_migration_item::dsl::error.eq(None::<String>),
+                    storage_migration_item::dsl::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+            Ok(true)
+        })
+    }
+
+    pub fn finish_item(
+        mm: &ModelManager,
+        id: &Uuid,
+        error: Option<String>,
+    ) -> Result<StorageMigrationItem> {
+        let mut conn = mm.conn()?;
+
+        let status = match error {
+            Some(_) => ITEM_FAILED,
+            None => ITEM_DONE,
+        };
+
+        diesel::update(storage_migration_item::dsl::storage_migration_item.find(id))
+            .set((
+                storage_migration_item::dsl::status.eq(status),
+                storage_migration_item::dsl::error.eq(error),
+                storage_migration_item::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<StorageMigrationItem>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Items that are pending or being copied.
+    pub fn count_open_items(mm: &ModelManager, migration_id: &Uuid) -> Result<i64> {
+        let mut conn = mm.conn()?;
+
+        storage_migration_item::dsl::storage_migration_item
+            .filter(storage_migration_item::dsl::migration_id.eq(migration_id))
+            .filter(
+                storage_migration_item::dsl::status.eq_any(vec![ITEM_PENDING, ITEM_IN_PROGRESS]),
+            )
+            .count()
+            .get_result::<i64>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Puts items that were being copied when the server stopped back in the queue.
+    pub fn reset_in_progress_items(mm: &ModelManager, migration_id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(
+            storage_migration_item::dsl::storage_migration_item
+                .filter(storage_migration_item::dsl::migration_id.eq(migration_id))
+                .filter(storage_migration_item::dsl::status.eq(ITEM_IN_PROGRESS)),
+        )
+        .set(storage_migration_item::dsl::status.eq(ITEM_PENDING))
+        .execute(&mut conn)
+        .map_err(Into::into)
+    }
+}
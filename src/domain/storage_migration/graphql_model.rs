@@ -0,0 +1,145 @@
+use async_graphql::{ComplexObject, Context, Enum, SimpleObject};
+use async_graphql_relay::{RelayNode, RelayNodeID, RelayNodeObject};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    db::ModelManager,
+    graphql::{node::Node, Error, Identifiable},
+    services::store::StorageBackend,
+};
+
+use super::db_model::{
+    StorageMigration as DbStorageMigration, StorageMigrationDao,
+    StorageMigrationItem as DbStorageMigrationItem, MIGRATION_COMPLETED, MIGRATION_FAILED,
+    MIGRATION_RUNNING,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum StorageMigrationStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<&str> for StorageMigrationStatus {
+    fn from(status: &str) -> Self {
+        match status {
+            MIGRATION_RUNNING => StorageMigrationStatus::Running,
+            MIGRATION_COMPLETED => StorageMigrationStatus::Completed,
+            MIGRATION_FAILED => StorageMigrationStatus::Failed,
+            _ => StorageMigrationStatus::Queued,
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TargetBackend {
+    Lust,
+    Local,
+    S3,
+}
+
+impl From<TargetBackend> for StorageBackend {
+    fn from(backend: TargetBackend) -> Self {
+        match backend {
+            TargetBackend::Lust => StorageBackend::Lust,
+            TargetBackend::Local => StorageBackend::Local,
+            TargetBackend::S3 => StorageBackend::S3,
+        }
+    }
+}
+
+#[derive(SimpleObject, RelayNodeObject, Debug, Clone)]
+#[graphql(complex)]
+#[relay(node_suffix = "sm")]
+pub struct StorageMigration {
+    pub id: RelayNodeID<Self>,
+    pub target_backend: String,
+    pub target_bucket: String,
+    pub delete_source: bool,
+    pub status: StorageMigrationStatus,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub failed_items: i32,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[ComplexObject]
+impl StorageMigration {
+    /// Images that could not be moved, with the reason.
+    async fn failures(&self, ctx: &Context<'_>) -> Result<Vec<StorageMigrationFailure>, Error> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext),
+        };
+        let items = StorageMigrationDao::get_failed_items(mm, &self.id.to_uuid())
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(items.into_iter().map(|item| item.into()).collect())
+    }
+}
+
+impl From<DbStorageMigration> for StorageMigration {
+    fn from(migration: DbStorageMigration) -> Self {
+        Self {
+            id: RelayNodeID::new(migration.id),
+            target_backend: migration.target_backend,
+            target_bucket: migration.target_bucket,
+            delete_source: migration.delete_source,
+            status: migration.status.as_str().into(),
+            total_items: migration.total_items,
+            processed_items: migration.processed_items,
+            failed_items: migration.failed_items,
+            created_at: migration.created_at,
+            updated_at: migration.updated_at,
+        }
+    }
+}
+
+impl Identifiable for StorageMigration {
+    fn get_id(&self) -> Uuid {
+        self.id.to_uuid()
+    }
+}
+
+#[async_trait]
+impl RelayNode for StorageMigration {
+    type TNode = Node;
+
+    async fn get(
+        ctx: async_graphql_relay::RelayContext,
+        id: RelayNodeID<Self>,
+    ) -> async_graphql::Result<Option<Self::TNode>> {
+        let mm = ctx.get::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let migration = StorageMigrationDao::get_by_id(mm, &id.to_uuid())
+            .map(|migration: DbStorageMigration| -> StorageMigration { migration.into() })
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(Some(migration.into()))
+    }
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct StorageMigrationFailure {
+    pub image_id: Uuid,
+    pub source_path: String,
+    pub error: Option<String>,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<DbStorageMigrationItem> for StorageMigrationFailure {
+    fn from(item: DbStorageMigrationItem) -> Self {
+        Self {
+            image_id: item.image_id,
+            source_path: item.source_path,
+            error: item.error,
+            updated_at: item.updated_at,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod db_model;
+mod graphql_model;
+mod mutation;
+mod query;
+mod subscription;
+
+pub use db_model::{
+    CreateStorageMigration, MovedImage, StorageMigration as DbStorageMigration,
+    StorageMigrationDao, StorageMigrationItem as DbStorageMigrationItem, MIGRATION_COMPLETED,
+    MIGRATION_FAILED, MIGRATION_RUNNING,
+};
+pub use graphql_model::{StorageMigration, StorageMigrationStatus};
+pub use mutation::StorageMigrationMutation;
+pub use query::StorageMigrationQuery;
+pub use subscription::StorageMigrationSubscription;
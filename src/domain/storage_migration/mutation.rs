@@ -0,0 +1,70 @@
+use async_graphql::*;
+use uuid::Uuid;
+
+use crate::{
+    config::config,
+    db::ModelManager,
+    graphql::{AdminGuard, Error},
+    jobs::MigrationQueue,
+    services::store::StorageBackend,
+};
+
+use super::{
+    db_model::{CreateStorageMigration, StorageMigration as DbStorageMigration},
+    graphql_model::TargetBackend,
+    StorageMigration,
+};
+
+#[derive(Default)]
+pub struct StorageMigrationMutation;
+
+#[Object]
+impl StorageMigrationMutation {
+    /// Moves every image that isn't there yet into `targetBucket` of `targetBackend`,
+    /// `LUST_BUCKET` when no bucket is given. With `deleteSource` the old objects are
+    /// removed once an image has been switched over.
+    #[graphql(guard = "AdminGuard")]
+    async fn create_storage_migration(
+        &self,
+        ctx: &Context<'_>,
+        target_backend: TargetBackend,
+        target_bucket: Option<String>,
+        delete_source: Option<bool>,
+    ) -> Result<StorageMigration> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let queue = match ctx.data_opt::<MigrationQueue>() {
+            Some(queue) => queue,
+            None => return Err(Error::MigrationQueueNotInContext.into()),
+        };
+
+        let target_bucket = target_bucket.unwrap_or_else(|| config().LUST_BUCKET.clone());
+        // the bucket is kept in `image.path`, see `StoreRouter`
+        if target_bucket.is_empty()
+            || target_bucket.contains(|c| matches!(c, ':' | '@' | '/' | '\\'))
+            || target_bucket.starts_with('.')
+        {
+            return Err(Error::InvalidBucket.into());
+        }
+
+        let backend: StorageBackend = target_backend.into();
+        let migration = queue
+            .create_migration(
+                mm,
+                CreateStorageMigration {
+                    id: Uuid::new_v4(),
+                    target_backend: backend.as_str().to_string(),
+                    target_bucket,
+                    delete_source: delete_source.unwrap_or(false),
+                },
+                backend,
+            )
+            .map(|migration: DbStorageMigration| -> StorageMigration { migration.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(migration)
+    }
+}
@@ -0,0 +1,66 @@
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+
+use crate::graphql::{uuidIdentifiedQuery, AdminGuard, ConnectionResult, CursorParams};
+use crate::{db::ModelManager, graphql::Error};
+
+use super::{db_model::StorageMigrationDao, DbStorageMigration, StorageMigration};
+
+#[derive(Default)]
+pub struct StorageMigrationQuery;
+
+#[Object]
+impl StorageMigrationQuery {
+    #[graphql(guard = "AdminGuard")]
+    async fn storage_migration(
+        &self,
+        ctx: &Context<'_>,
+        id: RelayNodeID<StorageMigration>,
+    ) -> Result<StorageMigration> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let migration = StorageMigrationDao::get_by_id(mm, &id.to_uuid())
+            .map(|migration: DbStorageMigration| -> StorageMigration { migration.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(migration)
+    }
+
+    #[graphql(guard = "AdminGuard")]
+    async fn storage_migrations(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> ConnectionResult<StorageMigration> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let migrations = StorageMigrationDao::list(mm)
+            .map(
+                |migrations: Vec<DbStorageMigration>| -> Vec<StorageMigration> {
+                    migrations
+                        .into_iter()
+                        .map(|migration| migration.into())
+                        .collect()
+                },
+            )
+            .map_err(|e| -> Error { e.into() })?;
+
+        uuidIdentifiedQuery(
+            migrations.into_iter(),
+            CursorParams::new(after, before, first, last),
+            10,
+        )
+        .await
+    }
+}
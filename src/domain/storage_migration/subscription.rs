@@ -0,0 +1,69 @@
+use async_graphql::Result;
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+use futures_util::stream::Stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::db::ModelManager;
+use crate::graphql::{AdminGuard, Error};
+use crate::jobs::MigrationQueue;
+
+use super::{StorageMigration, StorageMigrationDao, StorageMigrationStatus};
+
+#[derive(Default)]
+pub struct StorageMigrationSubscription;
+
+#[Subscription]
+impl StorageMigrationSubscription {
+    #[graphql(guard = "AdminGuard")]
+    async fn storage_migration_progress<'a>(
+        &'a self,
+        ctx: &'a Context<'a>,
+        id: RelayNodeID<StorageMigration>,
+    ) -> Result<impl Stream<Item = Result<StorageMigration>> + 'a> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let queue = match ctx.data_opt::<MigrationQueue>() {
+            Some(queue) => queue,
+            None => return Err(Error::MigrationQueueNotInContext.into()),
+        };
+
+        let migration_id = id.to_uuid();
+        let mut receiver = queue.subscribe();
+
+        let stream = async_stream::stream! {
+            let mut check = true;
+            loop {
+                if check {
+                    match StorageMigrationDao::get_by_id(mm, &migration_id) {
+                        Ok(migration) => {
+                            let migration: StorageMigration = migration.into();
+                            let is_finished = matches!(
+                                migration.status,
+                                StorageMigrationStatus::Completed | StorageMigrationStatus::Failed
+                            );
+                            yield Ok(migration);
+                            if is_finished {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            yield Err(Error::from(e).into());
+                            break;
+                        }
+                    }
+                }
+
+                check = match receiver.recv().await {
+                    Ok(updated_id) => updated_id == migration_id,
+                    Err(RecvError::Lagged(_)) => true,
+                    Err(RecvError::Closed) => break,
+                };
+            }
+        };
+        Ok(stream)
+    }
+}
@@ -0,0 +1,134 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::{ModelManager, Result};
+use crate::schema::storage_volume;
+
+pub const DEFAULT_VOLUME_NAME: &str = "default";
+
+/// A mount point raw albums are browsed and imported from, e.g. a NAS share.
+#[derive(Queryable, Deserialize, Debug, Clone)]
+#[diesel(table_name = storage_volume)]
+pub struct StorageVolume {
+    pub id: Uuid,
+    pub name: String,
+    pub mount_path: String,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl StorageVolume {
+    /// Absolute path of a path relative to the mount point.
+    pub fn path_of(&self, path: &str) -> String {
+        format!("{}/{}", self.mount_path.trim_end_matches('/'), path)
+    }
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = storage_volume)]
+pub struct CreateStorageVolume {
+    pub id: Uuid,
+    pub name: String,
+    pub mount_path: String,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: bool,
+}
+
+#[derive(AsChangeset, Serialize, Debug)]
+#[diesel(table_name = storage_volume)]
+pub struct UpdateStorageVolume {
+    pub name: Option<String>,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: Option<bool>,
+}
+
+pub struct StorageVolumeDao;
+
+impl StorageVolumeDao {
+    pub fn create(mm: &ModelManager, new_volume: CreateStorageVolume) -> Result<StorageVolume> {
+        let mut conn = mm.conn()?;
+
+        diesel::insert_into(storage_volume::dsl::storage_volume)
+            .values(&new_volume)
+            .get_result::<StorageVolume>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn get_by_id(mm: &ModelManager, id: &Uuid) -> Result<StorageVolume> {
+        let mut conn = mm.conn()?;
+
+        storage_volume::dsl::storage_volume
+            .find(id)
+            .first::<StorageVolume>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// `IMAGES_DIR` is always a volume, it is registered the first time it is needed.
+    pub fn get_default(mm: &ModelManager) -> Result<StorageVolume> {
+        let mut conn = mm.conn()?;
+        let mount_path = &config().IMAGES_DIR;
+
+        diesel::insert_into(storage_volume::dsl::storage_volume)
+            .values(&CreateStorageVolume {
+                id: Uuid::new_v4(),
+                name: DEFAULT_VOLUME_NAME.to_string(),
+                mount_path: mount_path.clone(),
+                capacity_bytes: None,
+                is_read_only: false,
+            })
+            .on_conflict(storage_volume::dsl::mount_path)
+            .do_nothing()
+            .execute(&mut conn)?;
+
+        storage_volume::dsl::storage_volume
+            .filter(storage_volume::dsl::mount_path.eq(mount_path))
+            .first::<StorageVolume>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// The given volume, or the default one when there is none.
+    pub fn resolve(mm: &ModelManager, id: Option<Uuid>) -> Result<StorageVolume> {
+        match id {
+            Some(id) => Self::get_by_id(mm, &id),
+            None => Self::get_default(mm),
+        }
+    }
+
+    pub fn list(mm: &ModelManager) -> Result<Vec<StorageVolume>> {
+        let mut conn = mm.conn()?;
+
+        storage_volume::dsl::storage_volume
+            .order(storage_volume::dsl::name.asc())
+            .load::<StorageVolume>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn update(
+        mm: &ModelManager,
+        id: &Uuid,
+        update_volume: UpdateStorageVolume,
+    ) -> Result<StorageVolume> {
+        let mut conn = mm.conn()?;
+
+        diesel::update(storage_volume::dsl::storage_volume.find(id))
+            .set((
+                &update_volume,
+                storage_volume::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<StorageVolume>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Images keep their rows, they just no longer record the volume.
+    pub fn delete(mm: &ModelManager, id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(storage_volume::dsl::storage_volume.find(id))
+            .execute(&mut conn)
+            .map_err(Into::into)
+    }
+}
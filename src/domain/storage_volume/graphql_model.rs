@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use async_graphql::{ComplexObject, InputObject, SimpleObject};
+use async_graphql_relay::{RelayNode, RelayNodeID, RelayNodeObject};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    db::ModelManager,
+    graphql::{node::Node, Error, Identifiable},
+};
+
+use super::db_model::{
+    CreateStorageVolume as DbCreateStorageVolume, StorageVolume as DbStorageVolume,
+    StorageVolumeDao, UpdateStorageVolume as DbUpdateStorageVolume,
+};
+
+#[derive(SimpleObject, RelayNodeObject, Debug, Clone)]
+#[graphql(complex)]
+#[relay(node_suffix = "sv")]
+pub struct StorageVolume {
+    pub id: RelayNodeID<Self>,
+    pub name: String,
+    pub mount_path: String,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+#[ComplexObject]
+impl StorageVolume {
+    /// Whether the mount point is there right now, NAS shares can go away.
+    async fn is_mounted(&self) -> bool {
+        tokio::fs::metadata(&self.mount_path)
+            .await
+            .map(|meta| meta.is_dir())
+            .unwrap_or(false)
+    }
+}
+
+impl From<DbStorageVolume> for StorageVolume {
+    fn from(volume: DbStorageVolume) -> Self {
+        Self {
+            id: RelayNodeID::new(volume.id),
+            name: volume.name,
+            mount_path: volume.mount_path,
+            capacity_bytes: volume.capacity_bytes,
+            is_read_only: volume.is_read_only,
+            created_at: volume.created_at,
+            updated_at: volume.updated_at,
+        }
+    }
+}
+
+impl Identifiable for StorageVolume {
+    fn get_id(&self) -> Uuid {
+        self.id.to_uuid()
+    }
+}
+
+#[async_trait]
+impl RelayNode for StorageVolume {
+    type TNode = Node;
+
+    async fn get(
+        ctx: async_graphql_relay::RelayContext,
+        id: RelayNodeID<Self>,
+    ) -> async_graphql::Result<Option<Self::TNode>> {
+        let mm = ctx.get::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let volume = StorageVolumeDao::get_by_id(mm, &id.to_uuid())
+            .map(|volume: DbStorageVolume| -> StorageVolume { volume.into() })
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(Some(volume.into()))
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateStorageVolume {
+    pub name: String,
+    /// Absolute path the volume is mounted at.
+    pub mount_path: String,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: Option<bool>,
+}
+
+impl CreateStorageVolume {
+    pub fn is_mounted(&self) -> bool {
+        let path = Path::new(&self.mount_path);
+        path.is_absolute() && path.is_dir()
+    }
+}
+
+impl From<CreateStorageVolume> for DbCreateStorageVolume {
+    fn from(val: CreateStorageVolume) -> Self {
+        DbCreateStorageVolume {
+            id: Uuid::new_v4(),
+            name: val.name,
+            mount_path: val.mount_path.trim_end_matches('/').to_string(),
+            capacity_bytes: val.capacity_bytes,
+            is_read_only: val.is_read_only.unwrap_or(false),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct UpdateStorageVolume {
+    pub name: Option<String>,
+    pub capacity_bytes: Option<i64>,
+    pub is_read_only: Option<bool>,
+}
+
+impl From<UpdateStorageVolume> for DbUpdateStorageVolume {
+    fn from(val: UpdateStorageVolume) -> Self {
+        DbUpdateStorageVolume {
+            name: val.name,
+            capacity_bytes: val.capacity_bytes,
+            is_read_only: val.is_read_only,
+        }
+    }
+}
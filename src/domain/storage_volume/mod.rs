@@ -0,0 +1,9 @@
+mod db_model;
+mod graphql_model;
+mod mutation;
+mod query;
+
+pub use db_model::{StorageVolume as DbStorageVolume, StorageVolumeDao};
+pub use graphql_model::StorageVolume;
+pub use mutation::StorageVolumeMutation;
+pub use query::StorageVolumeQuery;
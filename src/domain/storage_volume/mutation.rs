@@ -0,0 +1,82 @@
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+
+use crate::{
+    db::ModelManager,
+    graphql::{AdminGuard, Error},
+};
+
+use super::{
+    db_model::{StorageVolume as DbStorageVolume, StorageVolumeDao},
+    graphql_model::{CreateStorageVolume, UpdateStorageVolume},
+    StorageVolume,
+};
+
+#[derive(Default)]
+pub struct StorageVolumeMutation;
+
+#[Object]
+impl StorageVolumeMutation {
+    /// Registers a mount point, it has to be mounted already.
+    #[graphql(guard = "AdminGuard")]
+    async fn create_storage_volume(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateStorageVolume,
+    ) -> Result<StorageVolume> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        if !input.is_mounted() {
+            return Err(Error::VolumeNotMounted.into());
+        }
+
+        let volume = StorageVolumeDao::create(mm, input.into())
+            .map(|volume: DbStorageVolume| -> StorageVolume { volume.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(volume)
+    }
+
+    #[graphql(guard = "AdminGuard")]
+    async fn update_storage_volume(
+        &self,
+        ctx: &Context<'_>,
+        id: RelayNodeID<StorageVolume>,
+        input: UpdateStorageVolume,
+    ) -> Result<StorageVolume> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let volume = StorageVolumeDao::update(mm, &id.to_uuid(), input.into())
+            .map(|volume: DbStorageVolume| -> StorageVolume { volume.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(volume)
+    }
+
+    /// Only unregisters the volume, nothing on it is touched.
+    #[graphql(guard = "AdminGuard")]
+    async fn delete_storage_volume(
+        &self,
+        ctx: &Context<'_>,
+        id: RelayNodeID<StorageVolume>,
+    ) -> Result<bool> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let deleted =
+            StorageVolumeDao::delete(mm, &id.to_uuid()).map_err(|e| -> Error { e.into() })?;
+
+        Ok(deleted > 0)
+    }
+}
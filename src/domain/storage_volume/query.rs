@@ -0,0 +1,63 @@
+use async_graphql::*;
+use async_graphql_relay::RelayNodeID;
+
+use crate::graphql::{uuidIdentifiedQuery, AuthGuard, ConnectionResult, CursorParams};
+use crate::{db::ModelManager, graphql::Error};
+
+use super::{db_model::StorageVolumeDao, DbStorageVolume, StorageVolume};
+
+#[derive(Default)]
+pub struct StorageVolumeQuery;
+
+#[Object]
+impl StorageVolumeQuery {
+    #[graphql(guard = "AuthGuard")]
+    async fn storage_volume(
+        &self,
+        ctx: &Context<'_>,
+        id: RelayNodeID<StorageVolume>,
+    ) -> Result<StorageVolume> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        let volume = StorageVolumeDao::get_by_id(mm, &id.to_uuid())
+            .map(|volume: DbStorageVolume| -> StorageVolume { volume.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(volume)
+    }
+
+    /// All registered volumes, including the default `IMAGES_DIR` one.
+    #[graphql(guard = "AuthGuard")]
+    async fn storage_volumes(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> ConnectionResult<StorageVolume> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+
+        StorageVolumeDao::get_default(mm).map_err(|e| -> Error { e.into() })?;
+        let volumes = StorageVolumeDao::list(mm)
+            .map(|volumes: Vec<DbStorageVolume>| -> Vec<StorageVolume> {
+                volumes.into_iter().map(|volume| volume.into()).collect()
+            })
+            .map_err(|e| -> Error { e.into() })?;
+
+        uuidIdentifiedQuery(
+            volumes.into_iter(),
+            CursorParams::new(after, before, first, last),
+            10,
+        )
+        .await
+    }
+}
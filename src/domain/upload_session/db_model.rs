@@ -0,0 +1,105 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::db::{Error, ModelManager, Result};
+use crate::schema::upload_session;
+
+#[derive(Queryable, Deserialize, Debug)]
+#[diesel(table_name = upload_session)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub file_name: String,
+    pub upload_length: i64,
+    pub upload_offset: i64,
+    pub is_primary_album: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub link_duplicates: bool,
+    pub retention: String,
+}
+
+#[derive(Insertable, Serialize, Debug)]
+#[diesel(table_name = upload_session)]
+pub struct CreateUploadSession {
+    pub id: Uuid,
+    pub album_id: Uuid,
+    pub file_name: String,
+    pub upload_length: i64,
+    pub is_primary_album: bool,
+    pub link_duplicates: bool,
+    pub retention: String,
+}
+
+pub struct UploadSessionDao;
+
+impl UploadSessionDao {
+    pub fn create(mm: &ModelManager, new_session: CreateUploadSession) -> Result<UploadSession> {
+        let mut conn = mm.conn()?;
+
+        diesel::insert_into(upload_session::dsl::upload_session)
+            .values(&new_session)
+            .get_result::<UploadSession>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn get_by_id(mm: &ModelManager, id: &Uuid) -> Result<UploadSession> {
+        let mut conn = mm.conn()?;
+
+        upload_session::dsl::upload_session
+            .find(id)
+            .first::<UploadSession>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Runs `f` while holding the session's row lock, so requests for the same session
+    /// take turns, and saves the offset it returns before the lock is released.
+    pub fn update_offset_locked<T>(
+        mm: &ModelManager,
+        id: &Uuid,
+        f: impl FnOnce(&UploadSession) -> (i64, T),
+    ) -> Result<(UploadSession, T)> {
+        let mut conn = mm.conn()?;
+
+        conn.transaction(|conn| {
+            let session = upload_session::dsl::upload_session
+                .find(id)
+                .for_update()
+                .first::<UploadSession>(conn)?;
+
+            let (offset, res) = f(&session);
+            if offset == session.upload_offset {
+                return Ok((session, res));
+            }
+            let session = diesel::update(upload_session::dsl::upload_session.find(id))
+                .set((
+                    upload_session::dsl::upload_offset.eq(offset),
+                    upload_session::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ))
+                .get_result::<UploadSession>(conn)?;
+            Ok((session, res))
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+    }
+
+    /// Deletes the sessions nothing was uploaded to since `before`, returning their ids.
+    pub fn delete_stale(mm: &ModelManager, before: chrono::NaiveDateTime) -> Result<Vec<Uuid>> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(
+            upload_session::dsl::upload_session.filter(upload_session::dsl::updated_at.lt(before)),
+        )
+        .returning(upload_session::dsl::id)
+        .get_results::<Uuid>(&mut conn)
+        .map_err(Into::into)
+    }
+
+    pub fn delete(mm: &ModelManager, id: &Uuid) -> Result<usize> {
+        let mut conn = mm.conn()?;
+
+        diesel::delete(upload_session::dsl::upload_session.find(id))
+            .execute(&mut conn)
+            .map_err(Into::into)
+    }
+}
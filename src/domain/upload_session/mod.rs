@@ -0,0 +1,3 @@
+mod db_model;
+
+pub use db_model::{CreateUploadSession, UploadSession, UploadSessionDao};
@@ -0,0 +1,49 @@
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::ingest::{compensate_upload, Error, Result, ScannedFile};
+use crate::services::store::ImageStore;
+
+use super::db_model::{User, UserBmc};
+
+/// Stores the avatar in the profile bucket and deletes the one it replaces.
+pub async fn replace_avatar(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    user_id: &Uuid,
+    file: &ScannedFile,
+) -> Result<User> {
+    imagesize::blob_size(&file.head).map_err(|_| Error::BadImage)?;
+
+    let bucket = &config().LUST_PROFILE_BUCKET;
+    let previous = UserBmc::get_by_id(mm, user_id)?.avatar_path;
+    let stored = store.put_file(bucket, &file.path, &file.hash).await?;
+
+    let user = match UserBmc::set_avatar(mm, user_id, Some(&stored.image_id)) {
+        Ok(user) => user,
+        Err(e) => {
+            compensate_upload(mm, store, bucket, &stored.image_id).await;
+            return Err(e.into());
+        }
+    };
+
+    // the old avatar is an orphan now, a failed delete is left to the cleanup task
+    if let Some(previous) = previous {
+        compensate_upload(mm, store, bucket, &previous).await;
+    }
+    Ok(user)
+}
+
+pub async fn remove_avatar(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    user_id: &Uuid,
+) -> Result<User> {
+    let previous = UserBmc::get_by_id(mm, user_id)?.avatar_path;
+    let user = UserBmc::set_avatar(mm, user_id, None)?;
+    if let Some(previous) = previous {
+        compensate_upload(mm, store, &config().LUST_PROFILE_BUCKET, &previous).await;
+    }
+    Ok(user)
+}
@@ -18,6 +18,8 @@ pub struct User {
     pub is_admin: bool,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    /// Id of the avatar in the profile bucket.
+    pub avatar_path: Option<String>,
 }
 
 pub struct UserBmc;
@@ -48,6 +50,26 @@ impl UserBmc {
             .map_err(Into::into)
     }
 
+    pub fn set_avatar(mm: &ModelManager, id: &Uuid, avatar_path: Option<&str>) -> Result<User> {
+        let mut conn = mm.conn()?;
+        diesel::update(users::dsl::users.filter(users::dsl::id.eq(id)))
+            .set((
+                users::dsl::avatar_path.eq(avatar_path),
+                users::dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<User>(&mut conn)
+            .map_err(Into::into)
+    }
+
+    pub fn list_avatar_paths(mm: &ModelManager) -> Result<Vec<String>> {
+        let mut conn = mm.conn()?;
+        users::dsl::users
+            .filter(users::dsl::avatar_path.is_not_null())
+            .select(users::dsl::avatar_path.assume_not_null())
+            .load::<String>(&mut conn)
+            .map_err(Into::into)
+    }
+
     pub fn list(mm: &ModelManager) -> Result<Vec<User>> {
         let mut conn = mm.conn()?;
         users::dsl::users
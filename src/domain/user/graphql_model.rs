@@ -0,0 +1,96 @@
+use async_graphql::{ComplexObject, Enum, SimpleObject};
+use async_graphql_relay::{RelayNode, RelayNodeID, RelayNodeObject};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    db::ModelManager,
+    graphql::{node::Node, Error, Identifiable},
+};
+
+use super::db_model::{User as DbUser, UserBmc};
+
+/// Sizing presets of the profile bucket.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum AvatarSize {
+    #[default]
+    Small,
+    Medium,
+}
+
+impl AvatarSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AvatarSize::Small => "small",
+            AvatarSize::Medium => "medium",
+        }
+    }
+}
+
+#[derive(SimpleObject, RelayNodeObject, Debug, Clone)]
+#[graphql(complex)]
+#[relay(node_suffix = "us")]
+pub struct User {
+    pub id: RelayNodeID<Self>,
+    pub email: String,
+    pub is_admin: bool,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    #[graphql(skip)]
+    pub avatar_path: Option<String>,
+}
+
+#[ComplexObject]
+impl User {
+    /// Where the avatar is served from, uploaded with `PUT /api/avatar`.
+    async fn avatar_url(&self, #[graphql(default)] size: AvatarSize) -> Option<String> {
+        // the avatar id changes on every upload, so replaced avatars aren't served from caches
+        self.avatar_path.as_ref().map(|avatar_path| {
+            format!(
+                "/api/avatar/{}?size={}&v={}",
+                self.id.to_uuid(),
+                size.as_str(),
+                avatar_path
+            )
+        })
+    }
+}
+
+impl From<DbUser> for User {
+    fn from(user: DbUser) -> Self {
+        Self {
+            id: RelayNodeID::new(user.id),
+            email: user.email,
+            is_admin: user.is_admin,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            avatar_path: user.avatar_path,
+        }
+    }
+}
+
+impl Identifiable for User {
+    fn get_id(&self) -> Uuid {
+        self.id.to_uuid()
+    }
+}
+
+#[async_trait]
+impl RelayNode for User {
+    type TNode = Node;
+
+    async fn get(
+        ctx: async_graphql_relay::RelayContext,
+        id: RelayNodeID<Self>,
+    ) -> async_graphql::Result<Option<Self::TNode>> {
+        let mm = ctx.get::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let user = UserBmc::get_by_id(mm, &id.to_uuid())
+            .map(|user: DbUser| -> User { user.into() })
+            .map_err(|e| -> Error { e.into() })?;
+        Ok(Some(user.into()))
+    }
+}
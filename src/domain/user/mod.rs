@@ -1 +1,10 @@
+pub mod avatar;
 pub mod db_model;
+mod graphql_model;
+mod mutation;
+mod query;
+
+pub use db_model::{User as DbUser, UserBmc};
+pub use graphql_model::User;
+pub use mutation::UserMutation;
+pub use query::UserQuery;
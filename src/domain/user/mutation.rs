@@ -0,0 +1,41 @@
+use async_graphql::*;
+
+use crate::{
+    db::ModelManager,
+    graphql::{AuthGuard, Error},
+    services::store::Store,
+    web::ctx::Ctx,
+};
+
+use super::{avatar::remove_avatar, DbUser, User};
+
+#[derive(Default)]
+pub struct UserMutation;
+
+#[Object]
+impl UserMutation {
+    /// Removes the avatar of the logged in user, new ones are uploaded with `PUT /api/avatar`.
+    #[graphql(guard = "AuthGuard")]
+    async fn delete_avatar(&self, ctx: &Context<'_>) -> Result<User> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let store = match ctx.data_opt::<Store>() {
+            Some(store) => store.as_ref(),
+            None => return Err(Error::StoreNotInContext.into()),
+        };
+        let user_id = match ctx.data_opt::<Ctx>() {
+            Some(user_ctx) => user_ctx.user_id,
+            None => return Err(Error::AuthError.into()),
+        };
+
+        let user = remove_avatar(mm, store, &user_id)
+            .await
+            .map(|user: DbUser| -> User { user.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(user)
+    }
+}
@@ -0,0 +1,35 @@
+use async_graphql::*;
+
+use crate::{
+    db::ModelManager,
+    graphql::{AuthGuard, Error},
+    web::ctx::Ctx,
+};
+
+use super::{db_model::UserBmc, DbUser, User};
+
+#[derive(Default)]
+pub struct UserQuery;
+
+#[Object]
+impl UserQuery {
+    /// The logged in user.
+    #[graphql(guard = "AuthGuard")]
+    async fn me(&self, ctx: &Context<'_>) -> Result<User> {
+        let mm = ctx.data_opt::<ModelManager>();
+        let mm = match mm {
+            Some(mm) => mm,
+            None => return Err(Error::ModalManagerNotInContext.into()),
+        };
+        let user_id = match ctx.data_opt::<Ctx>() {
+            Some(user_ctx) => user_ctx.user_id,
+            None => return Err(Error::AuthError.into()),
+        };
+
+        let user = UserBmc::get_by_id(mm, &user_id)
+            .map(|user: DbUser| -> User { user.into() })
+            .map_err(|e| -> Error { e.into() })?;
+
+        Ok(user)
+    }
+}
@@ -3,16 +3,20 @@ use std::fmt::Display;
 use tracing::error;
 
 use crate::db::Error as DbError;
+use crate::ingest::Error as IngestError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
     ModalManagerNotInContext,
-    ClientNotInContext,
+    StoreNotInContext,
+    ImportQueueNotInContext,
+    MigrationQueueNotInContext,
 
     FailedToReadFile,
     FailedToReadDir,
+    FailedToWriteFile,
 
     FailedToDeleteFile,
 
@@ -29,6 +33,11 @@ pub enum Error {
     InvalidID,
 
     EntityExists,
+
+    VolumeNotMounted,
+    VolumeReadOnly,
+
+    InvalidBucket,
 }
 
 impl Display for Error {
@@ -41,14 +50,20 @@ impl Display for Error {
             | Error::NotFound(_) => write!(f, "Not found"),
             Error::GraphQlError(e) => write!(f, "{:?}", e),
             Error::DbError(_)
-            | Error::ClientNotInContext
+            | Error::StoreNotInContext
+            | Error::ImportQueueNotInContext
+            | Error::MigrationQueueNotInContext
             | Error::FailedToReadDir
             | Error::ModalManagerNotInContext => write!(f, "Internal server error"),
             Error::InvalidID => write!(f, "Invalid ID"),
             Error::EntityExists => write!(f, "Entity exists"),
             Error::FailedToReadFile => write!(f, "Failed to read file"),
+            Error::FailedToWriteFile => write!(f, "Failed to write file"),
             Error::FailedToDeleteFile => write!(f, "Failed to delete file"),
             Error::BadImage => write!(f, "Bad image"),
+            Error::VolumeNotMounted => write!(f, "Volume is not mounted"),
+            Error::VolumeReadOnly => write!(f, "Volume is read-only"),
+            Error::InvalidBucket => write!(f, "Invalid bucket name"),
         }
     }
 }
@@ -64,3 +79,18 @@ impl From<async_graphql::Error> for Error {
         Error::GraphQlError(e)
     }
 }
+
+impl From<IngestError> for Error {
+    fn from(e: IngestError) -> Self {
+        match e {
+            IngestError::BadImage => Error::BadImage,
+            IngestError::BadArchive => Error::GraphQlError("Bad archive".into()),
+            IngestError::ArchiveTooLarge => Error::GraphQlError("Archive too large".into()),
+            IngestError::EntityExists => Error::EntityExists,
+            IngestError::FailedToReadFile => Error::FailedToReadFile,
+            IngestError::FailedToWriteFile => Error::FailedToWriteFile,
+            IngestError::DbError(e) => Error::DbError(e),
+            IngestError::ServiceError(e) => Error::GraphQlError(e.into()),
+        }
+    }
+}
@@ -4,11 +4,12 @@ use axum::extract::State;
 use axum::response::{Html, IntoResponse};
 use axum::routing::get;
 use axum::Router;
-use reqwest::Client;
 
 use crate::web::ctx::Ctx;
 
 use crate::db::ModelManager;
+use crate::jobs::{ImportQueue, MigrationQueue};
+use crate::services::store::Store;
 use crate::web::Result;
 
 use super::schema::{create_schema, WooBooSchema};
@@ -16,19 +17,20 @@ use super::schema::{create_schema, WooBooSchema};
 #[derive(Clone)]
 pub struct GraphQlState {
     schema: WooBooSchema,
-    reqwest_client: Client,
+    store: Store,
 }
 
-pub fn routes(mm: ModelManager) -> Router {
-    let reqwest_client = Client::new();
-    let schema = create_schema(mm.clone(), reqwest_client.clone());
+pub fn routes(
+    mm: ModelManager,
+    store: Store,
+    import_queue: ImportQueue,
+    migration_queue: MigrationQueue,
+) -> Router {
+    let schema = create_schema(mm.clone(), store.clone(), import_queue, migration_queue);
     Router::new()
         .route("/", get(graphql_playground).post(graphql_handler))
         .route_service("/ws", GraphQLSubscription::new(schema.clone()))
-        .with_state(GraphQlState {
-            schema,
-            reqwest_client,
-        })
+        .with_state(GraphQlState { schema, store })
 }
 
 pub async fn graphql_playground() -> impl IntoResponse {
@@ -45,8 +47,8 @@ pub async fn graphql_handler(
 ) -> impl IntoResponse {
     let state = graph_ql_state;
     let builer_schema = match ctx {
-        Ok(ctx) => request.0.data(ctx).data(state.reqwest_client),
-        Err(_) => request.0.data(state.reqwest_client),
+        Ok(ctx) => request.0.data(ctx).data(state.store),
+        Err(_) => request.0.data(state.store),
     };
     let builder = state.schema.execute(builer_schema).await;
     let response = GraphQLResponse(builder.into());
@@ -1,3 +1,4 @@
+mod admin_guard;
 mod auth_guard;
 mod error;
 pub mod handler;
@@ -6,6 +7,7 @@ pub mod schema;
 mod string_cursor;
 mod uuid_cursor;
 
+pub use admin_guard::AdminGuard;
 pub use auth_guard::AuthGuard;
 pub use error::{Error, Result};
 pub use string_cursor::{
@@ -1,7 +1,10 @@
 use async_graphql::Interface;
 use async_graphql_relay::RelayInterface;
 
-use crate::domain::{album::Album, album_image_options::AlbumImage, image::Image};
+use crate::domain::{
+    album::Album, album_image_options::AlbumImage, image::Image, import_job::ImportJob,
+    storage_migration::StorageMigration, storage_volume::StorageVolume, user::User,
+};
 
 #[derive(Interface, RelayInterface)]
 #[graphql(field(name = "id", ty = "NodeGlobalID"))] // The 'NodeGlobalID' type comes from the 'RelayInterface' macro.
@@ -9,4 +12,8 @@ pub enum Node {
     Album(Album),
     Image(Image),
     AlbumImage(AlbumImage),
+    ImportJob(ImportJob),
+    StorageVolume(StorageVolume),
+    StorageMigration(StorageMigration),
+    User(User),
 }
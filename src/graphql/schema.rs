@@ -1,16 +1,28 @@
-use async_graphql::{Context, MergedObject, Schema, SimpleObject};
+use async_graphql::{Context, MergedObject, MergedSubscription, Schema, SimpleObject};
 use async_graphql_relay::{RelayContext, RelayNodeInterface};
-use reqwest::Client;
 use uuid::Uuid;
 
 use super::{error::Error, node::Node, uuid_cursor::Identifiable};
 use crate::domain::{
     album::{AlbumMutation, AlbumQuery},
     album_image_options::AlbumImageMutation,
+    consistency::ConsistencyMutation,
     image::{ImageMutation, ImageQuery, ImageSubscription},
+    import_job::{ImportJobMutation, ImportJobQuery, ImportJobSubscription},
+    integrity::{IntegrityMutation, IntegrityQuery},
     raw_album::{RawAlbumMutation, RawAlbumQuery},
+    storage_migration::{
+        StorageMigrationMutation, StorageMigrationQuery, StorageMigrationSubscription,
+    },
+    storage_volume::{StorageVolumeMutation, StorageVolumeQuery},
+    user::{UserMutation, UserQuery},
+};
+use crate::{
+    db::ModelManager,
+    jobs::{ImportQueue, MigrationQueue},
+    services::store::Store,
+    web::ctx::Ctx,
 };
-use crate::{db::ModelManager, web::ctx::Ctx};
 
 #[derive(Default)]
 struct DefaultQuery;
@@ -63,7 +75,17 @@ impl DefaultMutation {
 }
 
 #[derive(MergedObject, Default)]
-pub struct QueryRoot(DefaultQuery, RawAlbumQuery, AlbumQuery, ImageQuery);
+pub struct QueryRoot(
+    DefaultQuery,
+    RawAlbumQuery,
+    AlbumQuery,
+    ImageQuery,
+    ImportJobQuery,
+    StorageVolumeQuery,
+    StorageMigrationQuery,
+    IntegrityQuery,
+    UserQuery,
+);
 
 #[derive(MergedObject, Default)]
 pub struct MutationRoot(
@@ -72,17 +94,37 @@ pub struct MutationRoot(
     ImageMutation,
     AlbumImageMutation,
     RawAlbumMutation,
+    ImportJobMutation,
+    ConsistencyMutation,
+    StorageVolumeMutation,
+    StorageMigrationMutation,
+    IntegrityMutation,
+    UserMutation,
+);
+
+#[derive(MergedSubscription, Default)]
+pub struct SubscriptionRoot(
+    ImageSubscription,
+    ImportJobSubscription,
+    StorageMigrationSubscription,
 );
 
-pub type WooBooSchema = Schema<QueryRoot, MutationRoot, ImageSubscription>;
+pub type WooBooSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
-pub fn create_schema(mm: ModelManager, req_client: Client) -> WooBooSchema {
+pub fn create_schema(
+    mm: ModelManager,
+    store: Store,
+    import_queue: ImportQueue,
+    migration_queue: MigrationQueue,
+) -> WooBooSchema {
     Schema::build(
         QueryRoot::default(),
         MutationRoot::default(),
-        ImageSubscription,
+        SubscriptionRoot::default(),
     )
     .data(mm)
-    .data(req_client)
+    .data(store)
+    .data(import_queue)
+    .data(migration_queue)
     .finish()
 }
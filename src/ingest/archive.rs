@@ -0,0 +1,237 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Seek};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use tracing::{debug, error};
+
+use crate::config::config;
+
+use super::error::{Error, Result};
+
+pub struct ArchiveEntry {
+    /// Normalized path of the entry inside the archive.
+    pub name: String,
+    /// Where the entry was extracted to.
+    pub path: PathBuf,
+}
+
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn detect_kind(header: &[u8]) -> Option<ArchiveKind> {
+    if header.starts_with(b"PK\x03\x04") {
+        Some(ArchiveKind::Zip)
+    } else if header.starts_with(&[0x1F, 0x8B]) {
+        Some(ArchiveKind::TarGz)
+    } else if header.len() >= 262 && &header[257..262] == b"ustar" {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Extracts the regular files of a zip, tar or tar.gz archive into `dest`, in archive order.
+/// Entries that would end up outside of `dest` are skipped and links are never created.
+/// Archives with more than `ARCHIVE_MAX_ENTRIES` files, that unpack to more than
+/// `ARCHIVE_MAX_EXTRACTED_SIZE` bytes or that hold the same path twice are rejected.
+///
+/// This is blocking, run it with `spawn_blocking`.
+pub fn extract_archive(archive: &Path, dest: &Path) -> Result<Vec<ArchiveEntry>> {
+    let mut file = File::open(archive).map_err(|_| Error::FailedToReadFile)?;
+    let mut header = Vec::with_capacity(512);
+    (&file)
+        .take(512)
+        .read_to_end(&mut header)
+        .map_err(|_| Error::FailedToReadFile)?;
+    file.rewind().map_err(|_| Error::FailedToReadFile)?;
+
+    fs::create_dir_all(dest).map_err(|_| Error::FailedToWriteFile)?;
+
+    let mut extraction = Extraction::new(dest);
+    match detect_kind(&header).ok_or(Error::BadArchive)? {
+        ArchiveKind::Zip => extract_zip(file, &mut extraction)?,
+        ArchiveKind::Tar => extract_tar(BufReader::new(file), &mut extraction)?,
+        ArchiveKind::TarGz => extract_tar(GzDecoder::new(BufReader::new(file)), &mut extraction)?,
+    }
+    Ok(extraction.entries)
+}
+
+fn extract_zip(file: File, extraction: &mut Extraction) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(file).map_err(|_| Error::BadArchive)?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|_| Error::BadArchive)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        extraction.write_entry(&name, &mut entry)?;
+    }
+    Ok(())
+}
+
+fn extract_tar<R: Read>(reader: R, extraction: &mut Extraction) -> Result<()> {
+    let mut tar = tar::Archive::new(reader);
+
+    for entry in tar.entries().map_err(|_| Error::BadArchive)? {
+        let mut entry = entry.map_err(|_| Error::BadArchive)?;
+        // symlinks and hardlinks could point anywhere, so only plain files are taken
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = match entry.path() {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(_) => continue,
+        };
+        extraction.write_entry(&name, &mut entry)?;
+    }
+    Ok(())
+}
+
+/// What has been extracted so far, checked against the limits as entries are written.
+struct Extraction<'a> {
+    dest: &'a Path,
+    entries: Vec<ArchiveEntry>,
+    paths: HashSet<PathBuf>,
+    file_count: usize,
+    extracted_size: u64,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(dest: &'a Path) -> Self {
+        Self {
+            dest,
+            entries: Vec::new(),
+            paths: HashSet::new(),
+            file_count: 0,
+            extracted_size: 0,
+        }
+    }
+
+    fn write_entry(&mut self, name: &str, reader: &mut impl Read) -> Result<()> {
+        // skipped entries count too, an archive of millions of them is still a bomb
+        self.file_count += 1;
+        if self.file_count > config().ARCHIVE_MAX_ENTRIES {
+            error!("{:<12} - archive has too many entries", "ARCHIVE");
+            return Err(Error::ArchiveTooLarge);
+        }
+
+        let relative = match safe_relative_path(name) {
+            Some(relative) => relative,
+            None => {
+                error!("{:<12} - skipping unsafe entry {}", "ARCHIVE", name);
+                return Ok(());
+            }
+        };
+        // hidden files and macOS resource forks are never images
+        let skip = relative.components().any(|component| {
+            let part = component.as_os_str().to_string_lossy();
+            part.starts_with('.') || part == "__MACOSX"
+        });
+        if skip {
+            debug!("{:<12} - skipping {}", "ARCHIVE", name);
+            return Ok(());
+        }
+        // a second entry would silently replace the first one
+        if !self.paths.insert(relative.clone()) {
+            error!("{:<12} - duplicate entry {}", "ARCHIVE", name);
+            return Err(Error::BadArchive);
+        }
+
+        let path = self.dest.join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| Error::FailedToWriteFile)?;
+        }
+        let mut file = File::create(&path).map_err(|_| Error::FailedToWriteFile)?;
+        // headers can lie about sizes, so the limit is enforced on what is actually written
+        let remaining = config()
+            .ARCHIVE_MAX_EXTRACTED_SIZE
+            .saturating_sub(self.extracted_size);
+        let written = io::copy(&mut reader.take(remaining.saturating_add(1)), &mut file)
+            .map_err(|_| Error::BadArchive)?;
+        if written > remaining {
+            error!("{:<12} - archive unpacks to too much data", "ARCHIVE");
+            return Err(Error::ArchiveTooLarge);
+        }
+        self.extracted_size += written;
+
+        self.entries.push(ArchiveEntry {
+            name: relative.to_string_lossy().to_string(),
+            path,
+        });
+        Ok(())
+    }
+}
+
+/// Turns an entry name into a path relative to the extraction directory,
+/// or `None` if it is absolute or climbs out of it (zip-slip).
+fn safe_relative_path(name: &str) -> Option<PathBuf> {
+    let name = name.replace('\\', "/");
+    // only Windows parses drive letters as a prefix, archives made there may still have them
+    let bytes = name.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        return None;
+    }
+
+    let mut path = PathBuf::new();
+    for component in Path::new(&name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => (),
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::safe_relative_path;
+
+    #[test]
+    fn rejects_paths_leaving_the_destination() {
+        assert_eq!(safe_relative_path("../x"), None);
+        assert_eq!(safe_relative_path("a/../../x"), None);
+        assert_eq!(safe_relative_path("a/../x"), None);
+        assert_eq!(safe_relative_path("..\\x"), None);
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        assert_eq!(safe_relative_path("/x"), None);
+        assert_eq!(safe_relative_path("\\x"), None);
+        assert_eq!(safe_relative_path("C:\\x"), None);
+        assert_eq!(safe_relative_path("C:/x"), None);
+    }
+
+    #[test]
+    fn rejects_empty_names() {
+        assert_eq!(safe_relative_path(""), None);
+        assert_eq!(safe_relative_path("."), None);
+        assert_eq!(safe_relative_path("./"), None);
+    }
+
+    #[test]
+    fn normalizes_relative_paths() {
+        assert_eq!(safe_relative_path("./a"), Some(PathBuf::from("a")));
+        assert_eq!(
+            safe_relative_path("a/./b.jpg"),
+            Some(PathBuf::from("a/b.jpg"))
+        );
+        assert_eq!(
+            safe_relative_path("a\\b.jpg"),
+            Some(PathBuf::from("a/b.jpg"))
+        );
+        assert_eq!(
+            safe_relative_path("a//b.jpg"),
+            Some(PathBuf::from("a/b.jpg"))
+        );
+    }
+}
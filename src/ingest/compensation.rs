@@ -0,0 +1,43 @@
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::db::ModelManager;
+use crate::domain::lust_cleanup::{CreateLustCleanup, LustCleanupDao};
+use crate::services::store::ImageStore;
+
+/// Deletes a stored image that the database never got to reference.
+/// If that fails as well, the image is recorded so the cleanup task can retry later.
+pub async fn compensate_upload(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    bucket: &str,
+    lust_image_id: &str,
+) {
+    let error = match store.delete(bucket, lust_image_id).await {
+        Ok(()) => {
+            debug!("{:<12} - removed orphaned {}", "COMPENSATE", lust_image_id);
+            return;
+        }
+        Err(e) => e.to_string(),
+    };
+
+    error!(
+        "{:<12} - failed to remove orphaned {}: {}",
+        "COMPENSATE", lust_image_id, error
+    );
+    let res = LustCleanupDao::create(
+        mm,
+        CreateLustCleanup {
+            id: Uuid::new_v4(),
+            bucket: bucket.to_string(),
+            lust_image_id: lust_image_id.to_string(),
+            last_error: Some(error),
+        },
+    );
+    if let Err(e) = res {
+        error!(
+            "{:<12} - failed to record cleanup of {}: {}",
+            "COMPENSATE", lust_image_id, e
+        );
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt::Display;
+
+use crate::db::Error as DbError;
+use crate::services::error::Error as ServiceError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    BadImage,
+    BadArchive,
+    ArchiveTooLarge,
+    EntityExists,
+
+    FailedToReadFile,
+    FailedToWriteFile,
+
+    DbError(DbError),
+    ServiceError(ServiceError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::BadImage => write!(f, "Bad image"),
+            Error::BadArchive => write!(f, "Bad archive"),
+            Error::ArchiveTooLarge => write!(f, "Archive too large"),
+            Error::EntityExists => write!(f, "Entity exists"),
+            Error::FailedToReadFile => write!(f, "Failed to read file"),
+            Error::FailedToWriteFile => write!(f, "Failed to write file"),
+            Error::DbError(e) => write!(f, "Database error: {}", e),
+            Error::ServiceError(e) => write!(f, "{}", e.to_string()),
+        }
+    }
+}
+
+impl From<DbError> for Error {
+    fn from(e: DbError) -> Self {
+        Error::DbError(e)
+    }
+}
+
+impl From<ServiceError> for Error {
+    fn from(e: ServiceError) -> Self {
+        Error::ServiceError(e)
+    }
+}
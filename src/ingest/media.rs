@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use tokio::fs;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::domain::image::{DbImage, MEDIA_VIDEO};
+use crate::services::store::ImageStore;
+
+use super::{Error, Result};
+
+/// Detects video containers from their magic bytes and returns the file
+/// extension they are stored with.
+pub fn detect_video(bytes: &[u8]) -> Option<&'static str> {
+    // ISO base media (mp4, mov, m4v, 3gp) starts with a `ftyp` box
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return match &bytes[8..12] {
+            b"qt  " => Some("mov"),
+            b"M4V " | b"M4VH" | b"M4VP" => Some("m4v"),
+            b"3gp4" | b"3gp5" | b"3gp6" | b"3g2a" => Some("3gp"),
+            b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1"
+            | b"dash" | b"MSNV" | b"f4v " | b"NDAS" => Some("mp4"),
+            // other brands share the container but aren't videos, e.g. heic/avif stills,
+            // jpeg xl or camera raws like CR3
+            _ => None,
+        };
+    }
+    if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some("webm");
+    }
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"AVI " {
+        return Some("avi");
+    }
+    None
+}
+
+pub fn media_path(file_name: &str) -> String {
+    format!("{}/{}", config().MEDIA_DIR, file_name)
+}
+
+/// Stores a media file outside of Lust and returns its file name.
+pub async fn store_media(image_id: &Uuid, extension: &str, source: &Path) -> Result<String> {
+    fs::create_dir_all(&config().MEDIA_DIR).await.map_err(|e| {
+        error!("{:<12} - failed to create media dir: {}", "MEDIA", e);
+        Error::FailedToWriteFile
+    })?;
+
+    let file_name = format!("{}.{}", image_id, extension);
+    fs::copy(source, media_path(&file_name))
+        .await
+        .map_err(|e| {
+            error!("{:<12} - failed to store {}: {}", "MEDIA", file_name, e);
+            Error::FailedToWriteFile
+        })?;
+
+    Ok(file_name)
+}
+
+pub async fn remove_media(file_name: &str) -> Result<()> {
+    fs::remove_file(media_path(file_name)).await.map_err(|e| {
+        error!("{:<12} - failed to remove {}: {}", "MEDIA", file_name, e);
+        Error::FailedToWriteFile
+    })
+}
+
+/// Removes the file of an image from the image store, or from `MEDIA_DIR` for videos.
+pub async fn remove_stored(store: &dyn ImageStore, image: &DbImage) -> Result<()> {
+    if image.media_type == MEDIA_VIDEO {
+        return remove_media(&image.path).await;
+    }
+    store
+        .delete(&config().LUST_BUCKET, &image.path)
+        .await
+        .map_err(Into::into)
+}
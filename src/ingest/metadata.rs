@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+
+use exif::{Exif, In, Reader, Tag, Value};
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::domain::image_metadata::DbCreateImageMetadata;
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ExtractedMetadata {
+    pub taken_at: Option<chrono::NaiveDateTime>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub exposure_time: Option<String>,
+    pub f_number: Option<f64>,
+    pub iso: Option<i32>,
+    pub focal_length: Option<f64>,
+    pub orientation: Option<i32>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+    pub gps_altitude: Option<f64>,
+}
+
+impl ExtractedMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn fill_missing(&mut self, other: ExtractedMetadata) {
+        self.taken_at = self.taken_at.or(other.taken_at);
+        self.camera_make = self.camera_make.take().or(other.camera_make);
+        self.camera_model = self.camera_model.take().or(other.camera_model);
+        self.lens_model = self.lens_model.take().or(other.lens_model);
+        self.exposure_time = self.exposure_time.take().or(other.exposure_time);
+        self.f_number = self.f_number.or(other.f_number);
+        self.iso = self.iso.or(other.iso);
+        self.focal_length = self.focal_length.or(other.focal_length);
+        self.orientation = self.orientation.or(other.orientation);
+        self.gps_latitude = self.gps_latitude.or(other.gps_latitude);
+        self.gps_longitude = self.gps_longitude.or(other.gps_longitude);
+        self.gps_altitude = self.gps_altitude.or(other.gps_altitude);
+    }
+
+    pub fn into_create(self, image_id: Uuid) -> DbCreateImageMetadata {
+        DbCreateImageMetadata {
+            image_id,
+            taken_at: self.taken_at,
+            camera_make: self.camera_make,
+            camera_model: self.camera_model,
+            lens_model: self.lens_model,
+            exposure_time: self.exposure_time,
+            f_number: self.f_number,
+            iso: self.iso,
+            focal_length: self.focal_length,
+            orientation: self.orientation,
+            gps_latitude: self.gps_latitude,
+            gps_longitude: self.gps_longitude,
+            gps_altitude: self.gps_altitude,
+        }
+    }
+}
+
+/// Reads EXIF from the file container, falling back to an embedded XMP packet
+/// for anything EXIF doesn't carry.
+pub fn extract_metadata(bytes: &[u8]) -> ExtractedMetadata {
+    let metadata = Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .map(|exif| read_exif(&exif))
+        .unwrap_or_default();
+
+    with_xmp(metadata, bytes)
+}
+
+/// Like `extract_metadata`, but EXIF is read from the file, which only loads the parts
+/// of the container it needs. The XMP packet is looked for in `head`.
+pub async fn extract_file_metadata(path: &Path, head: &[u8]) -> ExtractedMetadata {
+    let path = path.to_path_buf();
+    let exif = tokio::task::spawn_blocking(move || {
+        let file = File::open(path).ok()?;
+        Reader::new()
+            .read_from_container(&mut BufReader::new(file))
+            .ok()
+    })
+    .await
+    .ok()
+    .flatten();
+    let metadata = exif.map(|exif| read_exif(&exif)).unwrap_or_default();
+
+    with_xmp(metadata, head)
+}
+
+fn with_xmp(mut metadata: ExtractedMetadata, bytes: &[u8]) -> ExtractedMetadata {
+    if let Some(xmp) = find_xmp_packet(bytes) {
+        metadata.fill_missing(read_xmp(&xmp));
+    }
+    metadata
+}
+
+// region:    --- EXIF
+
+fn read_exif(exif: &Exif) -> ExtractedMetadata {
+    ExtractedMetadata {
+        taken_at: exif_ascii(exif, Tag::DateTimeOriginal)
+            .or_else(|| exif_ascii(exif, Tag::DateTime))
+            .and_then(|v| chrono::NaiveDateTime::parse_from_str(&v, "%Y:%m:%d %H:%M:%S").ok()),
+        camera_make: exif_ascii(exif, Tag::Make),
+        camera_model: exif_ascii(exif, Tag::Model),
+        lens_model: exif_ascii(exif, Tag::LensModel),
+        exposure_time: exif_rational(exif, Tag::ExposureTime).map(|(num, denom)| {
+            if num < denom && num != 0 {
+                format!("1/{}", (denom as f64 / num as f64).round())
+            } else {
+                format!("{}", num as f64 / denom as f64)
+            }
+        }),
+        f_number: exif_f64(exif, Tag::FNumber),
+        iso: exif_uint(exif, Tag::PhotographicSensitivity).map(|v| v as i32),
+        focal_length: exif_f64(exif, Tag::FocalLength),
+        orientation: exif_uint(exif, Tag::Orientation).map(|v| v as i32),
+        gps_latitude: exif_gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S"),
+        gps_longitude: exif_gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W"),
+        gps_altitude: exif_f64(exif, Tag::GPSAltitude).map(|altitude| {
+            match exif_uint(exif, Tag::GPSAltitudeRef) {
+                Some(1) => -altitude,
+                _ => altitude,
+            }
+        }),
+    }
+}
+
+fn exif_ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(ref values) => values
+            .first()
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .filter(|v| !v.is_empty()),
+        _ => None,
+    }
+}
+
+fn exif_uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+fn exif_rational(exif: &Exif, tag: Tag) -> Option<(u32, u32)> {
+    match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(ref values) => values
+            .first()
+            .filter(|v| v.denom != 0)
+            .map(|v| (v.num, v.denom)),
+        _ => None,
+    }
+}
+
+fn exif_f64(exif: &Exif, tag: Tag) -> Option<f64> {
+    match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(ref values) => values
+            .first()
+            .filter(|v| v.denom != 0)
+            .map(|v| v.to_f64()),
+        Value::SRational(ref values) => values
+            .first()
+            .filter(|v| v.denom != 0)
+            .map(|v| v.to_f64()),
+        _ => None,
+    }
+}
+
+fn exif_gps_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative_ref: &str) -> Option<f64> {
+    let degrees = match exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(ref values) if values.len() == 3 && values.iter().all(|v| v.denom != 0) => {
+            values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0
+        }
+        _ => return None,
+    };
+
+    match exif_ascii(exif, ref_tag) {
+        Some(reference) if reference == negative_ref => Some(-degrees),
+        _ => Some(degrees),
+    }
+}
+
+// endregion: --- EXIF
+
+// region:    --- XMP
+
+fn find_xmp_packet(bytes: &[u8]) -> Option<String> {
+    const START: &[u8] = b"<x:xmpmeta";
+    const END: &[u8] = b"</x:xmpmeta>";
+
+    let start = bytes.windows(START.len()).position(|w| w == START)?;
+    let end = bytes[start..]
+        .windows(END.len())
+        .position(|w| w == END)
+        .map(|end| start + end + END.len())?;
+
+    Some(String::from_utf8_lossy(&bytes[start..end]).to_string())
+}
+
+// XMP stores properties either as attributes or as simple elements.
+fn xmp_value(xmp: &str, name: &str) -> Option<String> {
+    let name = regex::escape(name);
+    let attribute = Regex::new(&format!(r#"{}="([^"]*)""#, name)).ok()?;
+    let element = Regex::new(&format!(r"<{0}>([^<]*)</{0}>", name)).ok()?;
+
+    attribute
+        .captures(xmp)
+        .or_else(|| element.captures(xmp))
+        .map(|c| c[1].trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn read_xmp(xmp: &str) -> ExtractedMetadata {
+    ExtractedMetadata {
+        taken_at: xmp_value(xmp, "exif:DateTimeOriginal")
+            .or_else(|| xmp_value(xmp, "xmp:CreateDate"))
+            .or_else(|| xmp_value(xmp, "photoshop:DateCreated"))
+            .and_then(|v| parse_xmp_date(&v)),
+        camera_make: xmp_value(xmp, "tiff:Make"),
+        camera_model: xmp_value(xmp, "tiff:Model"),
+        lens_model: xmp_value(xmp, "exifEX:LensModel").or_else(|| xmp_value(xmp, "aux:Lens")),
+        exposure_time: xmp_value(xmp, "exif:ExposureTime"),
+        f_number: xmp_value(xmp, "exif:FNumber").and_then(|v| parse_xmp_rational(&v)),
+        iso: None,
+        focal_length: xmp_value(xmp, "exif:FocalLength").and_then(|v| parse_xmp_rational(&v)),
+        orientation: xmp_value(xmp, "tiff:Orientation").and_then(|v| v.parse().ok()),
+        gps_latitude: xmp_value(xmp, "exif:GPSLatitude").and_then(|v| parse_xmp_gps(&v)),
+        gps_longitude: xmp_value(xmp, "exif:GPSLongitude").and_then(|v| parse_xmp_gps(&v)),
+        gps_altitude: xmp_value(xmp, "exif:GPSAltitude").and_then(|v| parse_xmp_rational(&v)),
+    }
+}
+
+fn parse_xmp_date(value: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|date| date.naive_local())
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+}
+
+fn parse_xmp_rational(value: &str) -> Option<f64> {
+    match value.split_once('/') {
+        Some((num, denom)) => {
+            let num = num.parse::<f64>().ok()?;
+            let denom = denom.parse::<f64>().ok()?;
+            (denom != 0.0).then(|| num / denom)
+        }
+        None => value.parse().ok(),
+    }
+}
+
+// XMP GPS coordinates look like "48,51.4957N" or "48,51,29.7N".
+fn parse_xmp_gps(value: &str) -> Option<f64> {
+    let reference = value.chars().last()?;
+    let parts = value[..value.len() - reference.len_utf8()]
+        .split(',')
+        .map(|part| part.parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+
+    let degrees = parts
+        .iter()
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part / divisor)
+        .sum::<f64>();
+
+    match reference {
+        'S' | 'W' => Some(-degrees),
+        'N' | 'E' => Some(degrees),
+        _ => None,
+    }
+}
+
+// endregion: --- XMP
@@ -0,0 +1,402 @@
+use std::path::Path;
+
+use async_graphql::Enum;
+use imagesize::{blob_size, ImageSize};
+use serde::Deserialize;
+use tokio::fs;
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::image::{DbCreateImage, DbImage, ImageDao, MEDIA_IMAGE, MEDIA_VIDEO};
+use crate::domain::image_metadata::ImageMetadataDao;
+use crate::domain::storage_volume::DbStorageVolume;
+use crate::services::store::{fetch_object_hash, ImageStore};
+use crate::utils::delete_file;
+
+pub mod archive;
+mod compensation;
+mod error;
+pub mod media;
+pub mod metadata;
+pub mod raw;
+pub mod retention;
+pub mod scan;
+pub mod sidecar;
+pub mod staging;
+
+pub use compensation::compensate_upload;
+pub use error::{Error, Result};
+pub use retention::RetentionPolicy;
+pub use scan::{scan_file, ScannedFile};
+pub use sidecar::{find_sidecar, is_sidecar, Sidecar};
+
+use metadata::ExtractedMetadata;
+
+#[derive(Enum, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    #[default]
+    Reject,
+    Link,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IngestOptions {
+    pub album_id: Uuid,
+    pub is_primary_album: bool,
+    pub on_duplicate: DuplicatePolicy,
+    pub retention: RetentionPolicy,
+}
+
+pub struct NewImage {
+    pub title: String,
+    pub original_full_title: String,
+    pub file: ScannedFile,
+    pub order_index: Option<i32>,
+    /// Where the original lives, if it was imported from a storage volume.
+    pub source: Option<SourceFile>,
+    pub sidecar: Option<Sidecar>,
+}
+
+pub struct SourceFile {
+    pub volume_id: Uuid,
+    /// Absolute path of the original.
+    pub path: String,
+}
+
+pub struct Ingested {
+    pub image: DbImage,
+    pub linked: bool,
+}
+
+/// What goes to the store, RAW files are replaced by their preview.
+enum Content<'a> {
+    Bytes(Vec<u8>),
+    File(&'a ScannedFile),
+}
+
+pub async fn ingest_image(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    options: &IngestOptions,
+    new_image: NewImage,
+) -> Result<Ingested> {
+    let hash = new_image.file.hash.clone();
+
+    if let Some(existing) = ImageDao::get_by_content_hash(mm, &hash)? {
+        return match options.on_duplicate {
+            DuplicatePolicy::Reject => Err(Error::EntityExists),
+            DuplicatePolicy::Link => {
+                debug!(
+                    "{:<12} - {} is a duplicate of {}, linking",
+                    "INGEST", new_image.original_full_title, existing.id
+                );
+                let image = ImageDao::link_to_album(
+                    mm,
+                    &options.album_id,
+                    &existing.id,
+                    new_image.order_index,
+                    options.is_primary_album,
+                )?;
+                Ok(Ingested {
+                    image,
+                    linked: true,
+                })
+            }
+        };
+    }
+
+    if let Some(extension) = media::detect_video(&new_image.file.head) {
+        return ingest_video(mm, options, new_image, hash, extension).await;
+    }
+
+    // Lust can't decode RAW, so it gets the embedded preview and the RAW is always retained.
+    // The preview can sit anywhere in the file, so RAW files are still read whole.
+    let raw_format = raw::detect_raw(&new_image.original_full_title, &new_image.file.head);
+    let (preview, image_dimensions, embedded) = match raw_format {
+        Some(_) => {
+            let bytes = fs::read(&new_image.file.path)
+                .await
+                .map_err(|_| Error::FailedToReadFile)?;
+            let preview = raw::extract_preview(&bytes).ok_or(Error::BadImage)?;
+            let dimensions = match raw::raw_dimensions(&bytes) {
+                Some(dimensions) => dimensions,
+                None => blob_size(&preview).map_err(|_| Error::BadImage)?,
+            };
+            (
+                Some(preview),
+                dimensions,
+                metadata::extract_metadata(&bytes),
+            )
+        }
+        None => (
+            None,
+            image_dimensions(&new_image.file).await?,
+            metadata::extract_file_metadata(&new_image.file.path, &new_image.file.head).await,
+        ),
+    };
+    let metadata = sidecar_metadata(&new_image, embedded);
+
+    let image_id = Uuid::new_v4();
+    // Uploaded files have nothing to keep in place, so their originals get archived instead.
+    let archive = match (options.retention, &new_image.source) {
+        (RetentionPolicy::Archive, _) | (RetentionPolicy::Keep, None) => true,
+        (RetentionPolicy::Delete, _) => raw_format.is_some(),
+        _ => false,
+    };
+    let archived = match archive {
+        true => Some(
+            retention::archive_original(
+                &options.album_id,
+                &image_id,
+                &new_image.title,
+                &new_image.file.path,
+            )
+            .await?,
+        ),
+        false => None,
+    };
+    let archive_path = match (options.retention, &new_image.source) {
+        (RetentionPolicy::Keep, Some(source)) => Some(source.path.clone()),
+        _ => archived.clone(),
+    };
+    let volume_id = new_image.source.as_ref().map(|source| source.volume_id);
+    let content = match preview {
+        Some(preview) => Content::Bytes(preview),
+        None => Content::File(&new_image.file),
+    };
+
+    let res = store_image(
+        mm,
+        store,
+        options,
+        DbCreateImage {
+            id: image_id,
+            title: new_image.title,
+            description: new_image.sidecar.as_ref().and_then(Sidecar::description),
+            original_full_title: new_image.original_full_title,
+            path: String::new(),
+            width: Some(image_dimensions.width as i32),
+            height: Some(image_dimensions.height as i32),
+            is_uploaded: true,
+            content_hash: Some(hash),
+            archive_path,
+            media_type: MEDIA_IMAGE.to_string(),
+            raw_format: raw_format.map(str::to_string),
+            created_at: new_image.sidecar.as_ref().and_then(Sidecar::created_at),
+            volume_id,
+            lust_checksum: None,
+            stored_hash: None,
+        },
+        content,
+        new_image.order_index,
+    )
+    .await;
+    let image = match res {
+        Ok(image) => image,
+        Err(e) => {
+            if let Some(archived) = &archived {
+                retention::remove_archived(archived).await;
+            }
+            return Err(e);
+        }
+    };
+
+    pair_live_photo(mm, options, &image);
+    save_metadata(mm, &image, metadata);
+
+    Ok(Ingested {
+        image,
+        linked: false,
+    })
+}
+
+/// Videos are kept in `MEDIA_DIR` instead of Lust, which only handles images.
+async fn ingest_video(
+    mm: &ModelManager,
+    options: &IngestOptions,
+    new_image: NewImage,
+    hash: String,
+    extension: &str,
+) -> Result<Ingested> {
+    let metadata = sidecar_metadata(&new_image, ExtractedMetadata::default());
+    let image_id = Uuid::new_v4();
+    let file_name = media::store_media(&image_id, extension, &new_image.file.path).await?;
+
+    let res = ImageDao::create_with_album(
+        mm,
+        &options.album_id,
+        DbCreateImage {
+            id: image_id,
+            title: new_image.title,
+            description: new_image.sidecar.as_ref().and_then(Sidecar::description),
+            original_full_title: new_image.original_full_title,
+            path: file_name.clone(),
+            width: None,
+            height: None,
+            is_uploaded: true,
+            content_hash: Some(hash),
+            archive_path: None,
+            media_type: MEDIA_VIDEO.to_string(),
+            raw_format: None,
+            created_at: new_image.sidecar.as_ref().and_then(Sidecar::created_at),
+            volume_id: new_image.source.as_ref().map(|source| source.volume_id),
+            lust_checksum: None,
+            stored_hash: None,
+        },
+        new_image.order_index,
+        options.is_primary_album,
+    );
+    let image = match res {
+        Ok(image) => image,
+        Err(e) => {
+            let _ = media::remove_media(&file_name).await;
+            return Err(e.into());
+        }
+    };
+
+    pair_live_photo(mm, options, &image);
+    save_metadata(mm, &image, metadata);
+
+    Ok(Ingested {
+        image,
+        linked: false,
+    })
+}
+
+/// Reads the dimensions from the head of the file, or from the file itself
+/// when the header doesn't fit in the head.
+pub async fn image_dimensions(file: &ScannedFile) -> Result<ImageSize> {
+    if let Ok(dimensions) = blob_size(&file.head) {
+        return Ok(dimensions);
+    }
+    let path = file.path.clone();
+    tokio::task::spawn_blocking(move || imagesize::size(path))
+        .await
+        .map_err(|_| Error::FailedToReadFile)?
+        .map_err(|_| Error::BadImage)
+}
+
+/// Sidecar values win over the ones embedded in the file, they carry the user's edits.
+fn sidecar_metadata(new_image: &NewImage, embedded: ExtractedMetadata) -> ExtractedMetadata {
+    match &new_image.sidecar {
+        Some(sidecar) => {
+            let mut metadata = sidecar.metadata();
+            metadata.fill_missing(embedded);
+            metadata
+        }
+        None => embedded,
+    }
+}
+
+fn save_metadata(mm: &ModelManager, image: &DbImage, metadata: ExtractedMetadata) {
+    if metadata.is_empty() {
+        return;
+    }
+    if let Err(e) = ImageMetadataDao::upsert(mm, &metadata.into_create(image.id)) {
+        error!(
+            "{:<12} - failed to save metadata for {}: {}",
+            "INGEST", image.id, e
+        );
+    }
+}
+
+fn pair_live_photo(mm: &ModelManager, options: &IngestOptions, image: &DbImage) {
+    match ImageDao::pair_live_photo(mm, &options.album_id, image) {
+        Ok(Some(pair)) => debug!("{:<12} - paired {} with {}", "INGEST", image.id, pair),
+        Ok(None) => (),
+        Err(e) => error!("{:<12} - failed to pair {}: {}", "INGEST", image.id, e),
+    }
+}
+
+/// Puts the image in the store and records it, so that a failed database step
+/// doesn't leave an orphaned image behind in the store.
+async fn store_image(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    options: &IngestOptions,
+    mut new_image: DbCreateImage,
+    content: Content<'_>,
+    order_index: Option<i32>,
+) -> Result<DbImage> {
+    let bucket = &config().LUST_BUCKET;
+    let stored = match content {
+        Content::Bytes(bytes) => store.put(bucket, bytes).await?,
+        Content::File(file) => store.put_file(bucket, &file.path, &file.hash).await?,
+    };
+    new_image.path = stored.image_id.clone();
+    new_image.lust_checksum = stored.checksum;
+
+    // Stores that re-encode uploads, like Lust, only tell what they serve once it is
+    // fetched. Doing it now gives scrubbing a baseline from before anything could rot.
+    let stored_hash = match stored.hash {
+        Some(hash) => Ok(hash),
+        None => fetch_object_hash(store, bucket, &stored.image_id).await,
+    };
+    let res = match stored_hash {
+        Ok(stored_hash) => {
+            new_image.stored_hash = Some(stored_hash);
+            ImageDao::create_with_album(
+                mm,
+                &options.album_id,
+                new_image,
+                order_index,
+                options.is_primary_album,
+            )
+            .map_err(Error::from)
+        }
+        Err(e) => Err(e.into()),
+    };
+
+    match res {
+        Ok(image) => Ok(image),
+        Err(e) => {
+            compensate_upload(mm, store, bucket, &stored.image_id).await;
+            Err(e)
+        }
+    }
+}
+
+pub async fn ingest_raw_file(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    options: &IngestOptions,
+    volume: &DbStorageVolume,
+    album_path: &str,
+    file_name: &str,
+    order_index: Option<i32>,
+) -> Result<Ingested> {
+    let full_path = format!("{}/{}", album_path, file_name);
+    let file = scan_file(format!("{}/{}", volume.mount_path, full_path)).await?;
+    let album_dir = Path::new(&volume.mount_path).join(album_path);
+    let sidecar = find_sidecar(&album_dir, file_name).await;
+
+    let ingested = ingest_image(
+        mm,
+        store,
+        options,
+        NewImage {
+            title: file_name.to_string(),
+            original_full_title: full_path.clone(),
+            file,
+            order_index,
+            source: Some(SourceFile {
+                volume_id: volume.id,
+                path: volume.path_of(&full_path),
+            }),
+            sidecar,
+        },
+    )
+    .await?;
+
+    // read-only volumes keep their originals whatever the retention
+    if options.retention != RetentionPolicy::Keep && !volume.is_read_only {
+        if let Err(e) = delete_file(&volume.mount_path, &full_path).await {
+            error!("Failed to delete original file: {:?}", e);
+        }
+    }
+
+    Ok(ingested)
+}
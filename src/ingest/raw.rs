@@ -0,0 +1,244 @@
+use std::io::Cursor;
+
+use exif::{In, Reader, Tag};
+use imagesize::{blob_size, ImageSize};
+
+const JPEG_SOI: [u8; 3] = [0xFF, 0xD8, 0xFF];
+
+/// Detects the camera RAW formats we can ingest. DNG, NEF and ARW are plain
+/// TIFF containers, so the file extension decides which one it is.
+pub fn detect_raw(file_name: &str, bytes: &[u8]) -> Option<&'static str> {
+    let is_tiff = bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*");
+    if !is_tiff {
+        return None;
+    }
+    if bytes.len() >= 10 && &bytes[8..10] == b"CR" {
+        return Some("cr2");
+    }
+
+    let extension = file_name.rsplit_once('.')?.1.to_lowercase();
+    match extension.as_str() {
+        "dng" => Some("dng"),
+        "cr2" => Some("cr2"),
+        "nef" => Some("nef"),
+        "arw" => Some("arw"),
+        _ => None,
+    }
+}
+
+pub fn content_type(raw_format: &str) -> &'static str {
+    match raw_format {
+        "dng" => "image/x-adobe-dng",
+        "cr2" => "image/x-canon-cr2",
+        "nef" => "image/x-nikon-nef",
+        "arw" => "image/x-sony-arw",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Finds the largest embedded JPEG preview that Lust can decode.
+pub fn extract_preview(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut best: Option<(&[u8], usize)> = None;
+    let mut start = 0;
+
+    while let Some(offset) = bytes[start..]
+        .windows(JPEG_SOI.len())
+        .position(|window| window == JPEG_SOI)
+    {
+        let begin = start + offset;
+        let len = match jpeg_len(&bytes[begin..]) {
+            Some(len) => len,
+            None => {
+                start = begin + 2;
+                continue;
+            }
+        };
+
+        let jpeg = &bytes[begin..begin + len];
+        if let Ok(size) = blob_size(jpeg) {
+            let pixels = size.width * size.height;
+            if best.map(|(_, best)| pixels > best).unwrap_or(true) {
+                best = Some((jpeg, pixels));
+            }
+        }
+        start = begin + len;
+    }
+
+    best.map(|(jpeg, _)| jpeg.to_vec())
+}
+
+/// Walks the JPEG segments from SOI to EOI and returns the length of the stream.
+/// Lossless streams, which CR2 and some NEFs use for the sensor data itself, are rejected.
+fn jpeg_len(data: &[u8]) -> Option<usize> {
+    let mut pos = 2;
+    let mut decodable = false;
+
+    loop {
+        if pos + 1 >= data.len() || data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // fill byte
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            0xD9 => return decodable.then_some(pos + 2),
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            // baseline, extended and progressive huffman
+            0xC0..=0xC2 => decodable = true,
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return None,
+            _ => (),
+        }
+
+        if pos + 3 >= data.len() {
+            return None;
+        }
+        let segment_len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if segment_len < 2 {
+            return None;
+        }
+        pos += 2 + segment_len;
+
+        if marker == 0xDA {
+            // entropy-coded data runs until the next marker that isn't a stuffed byte or restart
+            loop {
+                if pos + 1 >= data.len() {
+                    return None;
+                }
+                let next = data[pos + 1];
+                if data[pos] == 0xFF && next != 0x00 && !(0xD0..=0xD7).contains(&next) {
+                    break;
+                }
+                pos += 1;
+            }
+        }
+    }
+}
+
+/// Reads the sensor dimensions the camera recorded in the RAW container.
+pub fn raw_dimensions(bytes: &[u8]) -> Option<ImageSize> {
+    let exif = Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()?;
+    let width = exif
+        .get_field(Tag::PixelXDimension, In::PRIMARY)?
+        .value
+        .get_uint(0)?;
+    let height = exif
+        .get_field(Tag::PixelYDimension, In::PRIMARY)?
+        .value
+        .get_uint(0)?;
+
+    Some(ImageSize {
+        width: width as usize,
+        height: height as usize,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_raw, extract_preview, jpeg_len};
+
+    const TIFF_HEADER: &[u8] = b"II*\0\x08\0\0\0";
+
+    /// A minimal JPEG stream with the given start-of-frame marker.
+    fn jpeg(sof: u8, width: u16, height: u16) -> Vec<u8> {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend_from_slice(&[0xFF, sof, 0x00, 0x11, 0x08]);
+        jpeg.extend_from_slice(&height.to_be_bytes());
+        jpeg.extend_from_slice(&width.to_be_bytes());
+        jpeg.extend_from_slice(&[0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01]);
+        jpeg.extend_from_slice(&[
+            0xFF, 0xDA, 0x00, 0x0C, 0x03, 0x01, 0x00, 0x02, 0x11, 0x03, 0x11, 0x00, 0x3F, 0x00,
+        ]);
+        // entropy-coded data with a stuffed byte and a restart marker
+        jpeg.extend_from_slice(&[0x12, 0xFF, 0x00, 0x34, 0xFF, 0xD0, 0x56]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    fn raw_with(previews: &[Vec<u8>]) -> Vec<u8> {
+        let mut raw = TIFF_HEADER.to_vec();
+        raw.extend_from_slice(&[0x00; 64]);
+        for preview in previews {
+            raw.extend_from_slice(preview);
+            raw.extend_from_slice(&[0x00; 16]);
+        }
+        raw
+    }
+
+    #[test]
+    fn finds_baseline_jpeg_after_tiff_data() {
+        let preview = jpeg(0xC0, 160, 120);
+        assert_eq!(jpeg_len(&preview), Some(preview.len()));
+        assert_eq!(
+            extract_preview(&raw_with(&[preview.clone()])),
+            Some(preview)
+        );
+    }
+
+    #[test]
+    fn picks_the_largest_preview() {
+        let small = jpeg(0xC0, 160, 120);
+        let large = jpeg(0xC2, 1600, 1200);
+        assert_eq!(
+            extract_preview(&raw_with(&[small.clone(), large.clone()])),
+            Some(large)
+        );
+    }
+
+    #[test]
+    fn rejects_lossless_streams() {
+        let lossless = jpeg(0xC3, 5184, 3456);
+        assert_eq!(jpeg_len(&lossless), None);
+        assert_eq!(extract_preview(&raw_with(&[lossless])), None);
+    }
+
+    #[test]
+    fn truncated_streams_are_rejected() {
+        let preview = jpeg(0xC0, 160, 120);
+        for len in 0..preview.len() {
+            assert_eq!(jpeg_len(&preview[..len]), None);
+            let mut raw = TIFF_HEADER.to_vec();
+            raw.extend_from_slice(&preview[..len]);
+            assert_eq!(extract_preview(&raw), None);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_segment_lengths() {
+        let mut preview = jpeg(0xC0, 160, 120);
+        preview[4..6].copy_from_slice(&[0x00, 0x01]);
+        assert_eq!(jpeg_len(&preview), None);
+        preview[4..6].copy_from_slice(&[0xFF, 0xFF]);
+        assert_eq!(jpeg_len(&preview), None);
+    }
+
+    #[test]
+    fn detects_cr2_by_its_magic() {
+        let cr2 = b"II*\0\x10\0\0\0CR\x02\0";
+        assert_eq!(detect_raw("IMG_0001", cr2), Some("cr2"));
+        assert_eq!(detect_raw("IMG_0001.jpg", cr2), Some("cr2"));
+    }
+
+    #[test]
+    fn detects_tiff_based_raws_by_extension() {
+        assert_eq!(detect_raw("a.dng", TIFF_HEADER), Some("dng"));
+        assert_eq!(detect_raw("a.NEF", TIFF_HEADER), Some("nef"));
+        assert_eq!(detect_raw("a.arw", b"MM\0*\0\0\0\x08"), Some("arw"));
+        assert_eq!(detect_raw("a.cr2", TIFF_HEADER), Some("cr2"));
+        assert_eq!(detect_raw("a.tif", TIFF_HEADER), None);
+        assert_eq!(detect_raw("a", TIFF_HEADER), None);
+    }
+
+    #[test]
+    fn ignores_files_that_arent_tiff() {
+        assert_eq!(detect_raw("a.dng", b"\xFF\xD8\xFF\xE0"), None);
+        assert_eq!(detect_raw("a.nef", b""), None);
+    }
+}
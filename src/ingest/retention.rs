@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use async_graphql::Enum;
+use serde::Deserialize;
+use tokio::fs;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::config::config;
+
+use super::{Error, Result};
+
+/// What happens to the original file once Lust has accepted it.
+/// Lust recompresses everything to jpeg, so `Keep` and `Archive` are the only
+/// ways to hold on to the untouched original.
+#[derive(Enum, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionPolicy {
+    #[default]
+    Delete,
+    Keep,
+    Archive,
+}
+
+impl RetentionPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionPolicy::Delete => "delete",
+            RetentionPolicy::Keep => "keep",
+            RetentionPolicy::Archive => "archive",
+        }
+    }
+}
+
+impl FromStr for RetentionPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "delete" => Ok(RetentionPolicy::Delete),
+            "keep" => Ok(RetentionPolicy::Keep),
+            "archive" => Ok(RetentionPolicy::Archive),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Copies the original into the archive directory and returns its absolute path.
+pub async fn archive_original(
+    album_id: &Uuid,
+    image_id: &Uuid,
+    title: &str,
+    original: &Path,
+) -> Result<String> {
+    let dir = format!("{}/{}", config().ARCHIVE_DIR, album_id);
+    fs::create_dir_all(&dir).await.map_err(|e| {
+        error!("{:<12} - failed to create archive dir: {}", "RETENTION", e);
+        Error::FailedToWriteFile
+    })?;
+
+    let path = format!("{}/{}_{}", dir, image_id, title);
+    fs::copy(original, &path).await.map_err(|e| {
+        error!("{:<12} - failed to archive {}: {}", "RETENTION", path, e);
+        Error::FailedToWriteFile
+    })?;
+
+    Ok(path)
+}
+
+pub async fn remove_archived(path: &str) {
+    if let Err(e) = fs::remove_file(path).await {
+        error!(
+            "{:<12} - failed to remove archived {}: {}",
+            "RETENTION", path, e
+        );
+    }
+}
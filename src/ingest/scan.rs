@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+
+use super::error::{Error, Result};
+
+/// Bytes kept from the start of a file, enough to tell its type and to read
+/// the dimensions and metadata of nearly every image.
+pub const HEAD_LEN: usize = 256 * 1024;
+const CHUNK_LEN: usize = 64 * 1024;
+
+/// A file on disk with what ingest needs to know about it, gathered chunk by chunk
+/// so the file never has to be held in memory.
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Sha256 of the whole file, kept as `image.content_hash`.
+    pub hash: String,
+    /// The first `HEAD_LEN` bytes.
+    pub head: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Scanner {
+    hasher: Sha256,
+    head: Vec<u8>,
+    size: u64,
+}
+
+impl Scanner {
+    fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+        let missing = HEAD_LEN.saturating_sub(self.head.len());
+        self.head
+            .extend_from_slice(&chunk[..missing.min(chunk.len())]);
+        self.size += chunk.len() as u64;
+    }
+
+    fn finish(self, path: PathBuf) -> ScannedFile {
+        ScannedFile {
+            path,
+            size: self.size,
+            hash: HEXLOWER.encode(&self.hasher.finalize()),
+            head: self.head,
+        }
+    }
+}
+
+pub async fn scan_file(path: impl Into<PathBuf>) -> Result<ScannedFile> {
+    let path = path.into();
+    let mut file = fs::File::open(&path)
+        .await
+        .map_err(|_| Error::FailedToReadFile)?;
+
+    let mut scanner = Scanner::default();
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        let read = file
+            .read(&mut chunk)
+            .await
+            .map_err(|_| Error::FailedToReadFile)?;
+        if read == 0 {
+            break;
+        }
+        scanner.update(&chunk[..read]);
+    }
+
+    Ok(scanner.finish(path))
+}
+
+/// Writes a request body to disk as it comes in, scanning it on the way.
+pub struct Spool {
+    file: fs::File,
+    path: PathBuf,
+    scanner: Scanner,
+}
+
+impl Spool {
+    pub fn new(file: fs::File, path: impl Into<PathBuf>) -> Self {
+        Self {
+            file,
+            path: path.into(),
+            scanner: Scanner::default(),
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.scanner.size
+    }
+
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file
+            .write_all(chunk)
+            .await
+            .map_err(|_| Error::FailedToWriteFile)?;
+        self.scanner.update(chunk);
+        Ok(())
+    }
+
+    pub async fn finish(mut self) -> Result<ScannedFile> {
+        self.file
+            .flush()
+            .await
+            .map_err(|_| Error::FailedToWriteFile)?;
+        Ok(self.scanner.finish(self.path))
+    }
+}
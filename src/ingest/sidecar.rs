@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use tokio::fs;
+use tracing::{debug, error};
+
+use super::metadata::ExtractedMetadata;
+
+/// Google Takeout cuts the media file name to this many characters
+/// before appending `.json`.
+const TAKEOUT_NAME_LIMIT: usize = 46;
+
+/// Metadata from a Google Takeout `.json` sidecar.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Sidecar {
+    title: Option<String>,
+    description: Option<String>,
+    photo_taken_time: Option<SidecarTime>,
+    creation_time: Option<SidecarTime>,
+    geo_data: Option<SidecarGeo>,
+    geo_data_exif: Option<SidecarGeo>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SidecarTime {
+    timestamp: String,
+}
+
+impl SidecarTime {
+    fn to_datetime(&self) -> Option<NaiveDateTime> {
+        let seconds = self.timestamp.parse::<i64>().ok()?;
+        NaiveDateTime::from_timestamp_opt(seconds, 0)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SidecarGeo {
+    latitude: f64,
+    longitude: f64,
+    altitude: Option<f64>,
+}
+
+impl Sidecar {
+    pub fn description(&self) -> Option<String> {
+        self.description
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+            .map(str::to_string)
+    }
+
+    /// When the file was added to the exported library.
+    pub fn created_at(&self) -> Option<NaiveDateTime> {
+        self.creation_time.as_ref()?.to_datetime()
+    }
+
+    pub fn metadata(&self) -> ExtractedMetadata {
+        // Takeout writes zeroes when there is no location
+        let geo = [&self.geo_data, &self.geo_data_exif]
+            .into_iter()
+            .flatten()
+            .find(|geo| geo.latitude != 0.0 || geo.longitude != 0.0);
+
+        ExtractedMetadata {
+            taken_at: self
+                .photo_taken_time
+                .as_ref()
+                .and_then(SidecarTime::to_datetime),
+            gps_latitude: geo.map(|geo| geo.latitude),
+            gps_longitude: geo.map(|geo| geo.longitude),
+            gps_altitude: geo.and_then(|geo| geo.altitude),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn is_sidecar(file_name: &str) -> bool {
+    file_name.to_lowercase().ends_with(".json")
+}
+
+/// Names Takeout may have given the sidecar of `file_name`, most specific first.
+fn sidecar_names(file_name: &str) -> Vec<String> {
+    let mut names = vec![
+        format!("{}.json", file_name),
+        format!("{}.supplemental-metadata.json", file_name),
+    ];
+    if file_name.chars().count() > TAKEOUT_NAME_LIMIT {
+        let truncated = file_name
+            .chars()
+            .take(TAKEOUT_NAME_LIMIT)
+            .collect::<String>();
+        names.push(format!("{}.json", truncated));
+    }
+    if let Some((stem, extension)) = file_name.rsplit_once('.') {
+        names.push(format!("{}.json", stem));
+        // edited copies share the sidecar of the original
+        if let Some(original) = stem.strip_suffix("-edited") {
+            names.push(format!("{}.{}.json", original, extension));
+        }
+    }
+    names
+}
+
+/// Looks for the sidecar of `file_name` next to it in `dir`.
+pub async fn find_sidecar(dir: &Path, file_name: &str) -> Option<Sidecar> {
+    for name in sidecar_names(file_name) {
+        let bytes = match fs::read(dir.join(&name)).await {
+            Ok(bytes) => bytes,
+            Err(_) => continue,
+        };
+        match serde_json::from_slice::<Sidecar>(&bytes) {
+            Ok(sidecar) => {
+                debug!(
+                    "{:<12} - using {} for {} ({:?})",
+                    "SIDECAR", name, file_name, sidecar.title
+                );
+                return Some(sidecar);
+            }
+            Err(e) => error!("{:<12} - failed to parse {}: {}", "SIDECAR", name, e),
+        }
+    }
+    None
+}
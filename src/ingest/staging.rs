@@ -0,0 +1,40 @@
+use tokio::fs;
+use tracing::error;
+
+use crate::config::config;
+
+use super::error::{Error, Result};
+
+pub fn staging_path(name: &str) -> String {
+    format!("{}/{}", &config().UPLOAD_STAGING_DIR, name)
+}
+
+pub async fn create_staging_file(name: &str) -> Result<()> {
+    fs::create_dir_all(&config().UPLOAD_STAGING_DIR)
+        .await
+        .map_err(|_| Error::FailedToWriteFile)?;
+    fs::File::create(staging_path(name))
+        .await
+        .map_err(|_| Error::FailedToWriteFile)?;
+    Ok(())
+}
+
+pub async fn open_staging_file(name: &str) -> Result<fs::File> {
+    fs::OpenOptions::new()
+        .append(true)
+        .open(staging_path(name))
+        .await
+        .map_err(|_| Error::FailedToWriteFile)
+}
+
+pub async fn remove_staging_file(name: &str) {
+    if let Err(e) = fs::remove_file(staging_path(name)).await {
+        error!("{:<12} - failed to delete staging file: {}", "STAGING", e);
+    }
+}
+
+pub async fn remove_staging_dir(name: &str) {
+    if let Err(e) = fs::remove_dir_all(staging_path(name)).await {
+        error!("{:<12} - failed to delete staging dir: {}", "STAGING", e);
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use tracing::{debug, error, info};
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::lust_cleanup::LustCleanupDao;
+use crate::services::store::{ImageStore, Store};
+
+/// Periodically retries deleting orphaned stored images that failed to be
+/// removed during ingest compensation.
+pub fn start_lust_cleanup(mm: ModelManager, store: Store) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(config().LUST_CLEANUP_INTERVAL.max(1)));
+        loop {
+            interval.tick().await;
+            retry_pending(&mm, store.as_ref()).await;
+        }
+    });
+}
+
+async fn retry_pending(mm: &ModelManager, store: &dyn ImageStore) {
+    let pending = match LustCleanupDao::list(mm) {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("{:<12} - failed to load pending cleanups: {}", "CLEANUP", e);
+            return;
+        }
+    };
+
+    for cleanup in pending {
+        let res = store.delete(&cleanup.bucket, &cleanup.lust_image_id).await;
+        let res = match res {
+            Err(e) if !e.is_not_found() => {
+                debug!(
+                    "{:<12} - retry of {} failed: {}",
+                    "CLEANUP",
+                    cleanup.lust_image_id,
+                    e.to_string()
+                );
+                LustCleanupDao::record_failure(mm, &cleanup.id, e.to_string()).map(|_| ())
+            }
+            // deleted, or already gone so there is nothing left to clean up
+            _ => LustCleanupDao::delete(mm, &cleanup.id).map(|_| ()),
+        };
+
+        match res {
+            Ok(()) => info!("{:<12} - processed {}", "CLEANUP", cleanup.lust_image_id),
+            Err(e) => error!("{:<12} - failed to update {}: {}", "CLEANUP", cleanup.id, e),
+        }
+    }
+}
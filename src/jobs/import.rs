@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use futures_util::StreamExt;
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::{ModelManager, Result};
+use crate::domain::import_job::{
+    CreateImportJob, DbImportJob, ImportJobDao, JOB_COMPLETED, JOB_FAILED, JOB_RUNNING,
+};
+use crate::domain::storage_volume::StorageVolumeDao;
+use crate::ingest::{ingest_raw_file, DuplicatePolicy, IngestOptions};
+use crate::services::store::{ImageStore, Store};
+
+type JobReceiver = Arc<Mutex<mpsc::UnboundedReceiver<Uuid>>>;
+
+/// Queue of persisted import jobs processed by a pool of background workers.
+/// Every change of a job's progress is broadcast with the job id.
+#[derive(Clone)]
+pub struct ImportQueue {
+    sender: mpsc::UnboundedSender<Uuid>,
+    progress: broadcast::Sender<Uuid>,
+}
+
+impl ImportQueue {
+    pub fn start(mm: ModelManager, store: Store) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel::<Uuid>();
+        let (progress, _) = broadcast::channel(256);
+        let receiver: JobReceiver = Arc::new(Mutex::new(receiver));
+
+        for worker_id in 0..config().IMPORT_WORKERS.max(1) {
+            tokio::spawn(run_worker(
+                worker_id,
+                mm.clone(),
+                store.clone(),
+                receiver.clone(),
+                progress.clone(),
+            ));
+        }
+
+        let queue = Self { sender, progress };
+        queue.resume_unfinished(&mm);
+        queue
+    }
+
+    /// Persists a job with one item per file and queues it right away.
+    pub fn create_job(
+        &self,
+        mm: &ModelManager,
+        new_job: CreateImportJob,
+        file_names: Vec<String>,
+    ) -> Result<DbImportJob> {
+        let job = ImportJobDao::create_with_items(mm, new_job, file_names)?;
+        self.enqueue(job.id);
+        Ok(job)
+    }
+
+    pub fn enqueue(&self, job_id: Uuid) {
+        if self.sender.send(job_id).is_err() {
+            error!("{:<12} - failed to queue import job {}", "IMPORT", job_id);
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Uuid> {
+        self.progress.subscribe()
+    }
+
+    fn resume_unfinished(&self, mm: &ModelManager) {
+        let jobs = match ImportJobDao::list_unfinished(mm) {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                error!("{:<12} - failed to load unfinished jobs: {}", "IMPORT", e);
+                return;
+            }
+        };
+
+        for job in jobs {
+            if let Err(e) = ImportJobDao::reset_in_progress_items(mm, &job.id) {
+                error!("{:<12} - failed to reset job {}: {}", "IMPORT", job.id, e);
+                continue;
+            }
+            info!("{:<12} - resuming import job {}", "IMPORT", job.id);
+            self.enqueue(job.id);
+        }
+    }
+}
+
+async fn run_worker(
+    worker_id: usize,
+    mm: ModelManager,
+    store: Store,
+    receiver: JobReceiver,
+    progress: broadcast::Sender<Uuid>,
+) {
+    loop {
+        let job_id = receiver.lock().await.recv().await;
+        let job_id = match job_id {
+            Some(job_id) => job_id,
+            None => break,
+        };
+
+        debug!(
+            "{:<12} - worker {} picked up job {}",
+            "IMPORT", worker_id, job_id
+        );
+        if let Err(e) = process_job(&mm, store.as_ref(), &progress, &job_id).await {
+            error!("{:<12} - import job {} failed: {}", "IMPORT", job_id, e);
+        }
+    }
+}
+
+async fn process_job(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    progress: &broadcast::Sender<Uuid>,
+    job_id: &Uuid,
+) -> Result<()> {
+    let job = ImportJobDao::set_status(mm, job_id, JOB_RUNNING)?;
+    let _ = progress.send(job.id);
+
+    let res = match import_items(mm, store, progress, &job).await {
+        Ok(()) => ImportJobDao::count_open_items(mm, job_id),
+        Err(e) => Err(e),
+    };
+
+    match res {
+        // another worker is still importing items of a resumed job and finishes it
+        Ok(open) if open > 0 => Ok(()),
+        Ok(_) => {
+            ImportJobDao::set_status(mm, job_id, JOB_COMPLETED)?;
+            let _ = progress.send(*job_id);
+            Ok(())
+        }
+        Err(e) => {
+            if let Err(status_error) = ImportJobDao::set_status(mm, job_id, JOB_FAILED) {
+                error!(
+                    "{:<12} - failed to mark job {} as failed: {}",
+                    "IMPORT", job_id, status_error
+                );
+            }
+            let _ = progress.send(*job_id);
+            Err(e)
+        }
+    }
+}
+
+/// Imports items of the job until none are left to claim. A database error stops
+/// the claiming, but the items already claimed are still finished before it is returned.
+async fn import_items(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    progress: &broadcast::Sender<Uuid>,
+    job: &DbImportJob,
+) -> Result<()> {
+    let volume = StorageVolumeDao::resolve(mm, job.volume_id)?;
+
+    let options = IngestOptions {
+        album_id: job.album_id,
+        is_primary_album: job.is_primary_album,
+        on_duplicate: match job.link_duplicates {
+            true => DuplicatePolicy::Link,
+            false => DuplicatePolicy::Reject,
+        },
+        retention: job.retention.parse().unwrap_or_default(),
+    };
+
+    // Items are claimed lazily, only when a slot frees up, so other workers can
+    // still pick up the rest of a resumed job.
+    let mut claim_error = None;
+    let items =
+        futures_util::stream::iter(std::iter::from_fn(|| {
+            match ImportJobDao::claim_next_item(mm, &job.id) {
+                Ok(item) => item,
+                Err(e) => {
+                    error!(
+                        "{:<12} - failed to claim item of job {}: {}",
+                        "IMPORT", job.id, e
+                    );
+                    claim_error = Some(e);
+                    None
+                }
+            }
+        }));
+
+    let options = &options;
+    let volume = &volume;
+    let album_path = job.album_path.as_str();
+    let mut results = items
+        .map(move |item| {
+            let order_index = job.order_offset + item.order_index;
+            async move {
+                let res = ingest_raw_file(
+                    mm,
+                    store,
+                    options,
+                    volume,
+                    album_path,
+                    &item.file_name,
+                    Some(order_index),
+                )
+                .await;
+                (item, res)
+            }
+        })
+        .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1));
+
+    let mut failure = None;
+    while let Some((item, res)) = results.next().await {
+        let finished = match res {
+            Ok(ingested) => ImportJobDao::finish_item(mm, &item.id, Some(ingested.image.id), None),
+            Err(e) => ImportJobDao::finish_item(mm, &item.id, None, Some(e.to_string())),
+        }
+        .and_then(|_| ImportJobDao::refresh_progress(mm, &job.id));
+
+        if let Err(e) = finished {
+            error!(
+                "{:<12} - failed to finish item {} of job {}: {}",
+                "IMPORT", item.id, job.id, e
+            );
+            failure.get_or_insert(e);
+        }
+        let _ = progress.send(job.id);
+    }
+    drop(results);
+
+    match claim_error.or(failure) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,386 @@
+use axum::body::Body;
+use data_encoding::HEXLOWER;
+use futures_util::StreamExt;
+use imagesize::blob_size;
+use sha2::{Digest, Sha256};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::{ModelManager, Result};
+use crate::domain::image::ImageDao;
+use crate::domain::storage_migration::{
+    CreateStorageMigration, DbStorageMigration, DbStorageMigrationItem, MovedImage,
+    StorageMigrationDao, MIGRATION_COMPLETED, MIGRATION_FAILED, MIGRATION_RUNNING,
+};
+use crate::ingest::scan::{ScannedFile, Spool, HEAD_LEN};
+use crate::ingest::staging::{
+    create_staging_file, open_staging_file, remove_staging_file, staging_path,
+};
+use crate::ingest::{compensate_upload, image_dimensions};
+use crate::services::store::{
+    decode_location, fetch_object_hash, GetOptions, ImageStore, StorageBackend, Store,
+};
+
+/// Queue of persisted storage migrations, worked through one at a time by a
+/// background worker. Every change of a migration's progress is broadcast with its id.
+#[derive(Clone)]
+pub struct MigrationQueue {
+    sender: mpsc::UnboundedSender<Uuid>,
+    progress: broadcast::Sender<Uuid>,
+}
+
+impl MigrationQueue {
+    pub fn start(mm: ModelManager, store: Store) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel::<Uuid>();
+        let (progress, _) = broadcast::channel(256);
+
+        tokio::spawn(run_worker(mm.clone(), store, receiver, progress.clone()));
+
+        let queue = Self { sender, progress };
+        queue.resume_unfinished(&mm);
+        queue
+    }
+
+    /// Persists a migration with one item per image not yet in the target bucket
+    /// and queues it right away.
+    pub fn create_migration(
+        &self,
+        mm: &ModelManager,
+        new_migration: CreateStorageMigration,
+        target: StorageBackend,
+    ) -> Result<DbStorageMigration> {
+        let images = StorageMigrationDao::list_image_paths(mm)?
+            .into_iter()
+            .filter(|(_, path)| {
+                let (backend, bucket, _) = decode_location(path);
+                backend != target
+                    || bucket.unwrap_or(config().LUST_BUCKET.as_str())
+                        != new_migration.target_bucket
+            })
+            .collect();
+
+        let migration = StorageMigrationDao::create_with_items(mm, new_migration, images)?;
+        self.enqueue(migration.id);
+        Ok(migration)
+    }
+
+    pub fn enqueue(&self, migration_id: Uuid) {
+        if self.sender.send(migration_id).is_err() {
+            error!(
+                "{:<12} - failed to queue storage migration {}",
+                "MIGRATION", migration_id
+            );
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Uuid> {
+        self.progress.subscribe()
+    }
+
+    fn resume_unfinished(&self, mm: &ModelManager) {
+        let migrations = match StorageMigrationDao::list_unfinished(mm) {
+            Ok(migrations) => migrations,
+            Err(e) => {
+                error!(
+                    "{:<12} - failed to load unfinished migrations: {}",
+                    "MIGRATION", e
+                );
+                return;
+            }
+        };
+
+        for migration in migrations {
+            if let Err(e) = StorageMigrationDao::reset_in_progress_items(mm, &migration.id) {
+                error!(
+                    "{:<12} - failed to reset migration {}: {}",
+                    "MIGRATION", migration.id, e
+                );
+                continue;
+            }
+            info!(
+                "{:<12} - resuming storage migration {}",
+                "MIGRATION", migration.id
+            );
+            self.enqueue(migration.id);
+        }
+    }
+}
+
+async fn run_worker(
+    mm: ModelManager,
+    store: Store,
+    mut receiver: mpsc::UnboundedReceiver<Uuid>,
+    progress: broadcast::Sender<Uuid>,
+) {
+    while let Some(migration_id) = receiver.recv().await {
+        debug!("{:<12} - picked up migration {}", "MIGRATION", migration_id);
+        if let Err(e) = process_migration(&mm, store.as_ref(), &progress, &migration_id).await {
+            error!(
+                "{:<12} - storage migration {} failed: {}",
+                "MIGRATION", migration_id, e
+            );
+        }
+    }
+}
+
+async fn process_migration(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    progress: &broadcast::Sender<Uuid>,
+    migration_id: &Uuid,
+) -> Result<()> {
+    let migration = StorageMigrationDao::set_status(mm, migration_id, MIGRATION_RUNNING)?;
+    let _ = progress.send(migration.id);
+
+    let res = match migrate_items(mm, store, progress, &migration).await {
+        Ok(()) => StorageMigrationDao::count_open_items(mm, migration_id),
+        Err(e) => Err(e),
+    };
+
+    let status = match &res {
+        Ok(0) => MIGRATION_COMPLETED,
+        _ => MIGRATION_FAILED,
+    };
+    if let Err(e) = StorageMigrationDao::set_status(mm, migration_id, status) {
+        error!(
+            "{:<12} - failed to set status of migration {}: {}",
+            "MIGRATION", migration_id, e
+        );
+    }
+    let _ = progress.send(*migration_id);
+
+    match res {
+        Ok(0) => Ok(()),
+        Ok(open) => {
+            error!(
+                "{:<12} - migration {} stopped with {} items left",
+                "MIGRATION", migration_id, open
+            );
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Migrates items until none are left to claim. A database error stops the claiming,
+/// but the items already claimed are still finished before it is returned.
+async fn migrate_items(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    progress: &broadcast::Sender<Uuid>,
+    migration: &DbStorageMigration,
+) -> Result<()> {
+    let mut claim_error = None;
+    let items =
+        futures_util::stream::iter(std::iter::from_fn(
+            || match StorageMigrationDao::claim_next_item(mm, &migration.id) {
+                Ok(item) => item,
+                Err(e) => {
+                    error!(
+                        "{:<12} - failed to claim item of migration {}: {}",
+                        "MIGRATION", migration.id, e
+                    );
+                    claim_error = Some(e);
+                    None
+                }
+            },
+        ));
+
+    let mut results = items
+        .map(|item| async move {
+            let res = migrate_item(mm, store, migration, &item).await;
+            (item, res)
+        })
+        .buffer_unordered(config().UPLOAD_CONCURRENCY.max(1));
+
+    let mut failure = None;
+    while let Some((item, res)) = results.next().await {
+        let finished = match res {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!(
+                    "{:<12} - failed to migrate image {}: {}",
+                    "MIGRATION", item.image_id, e
+                );
+                StorageMigrationDao::finish_item(mm, &item.id, Some(e)).map(|_| ())
+            }
+        }
+        .and_then(|_| StorageMigrationDao::refresh_progress(mm, &migration.id));
+
+        if let Err(e) = finished {
+            error!(
+                "{:<12} - failed to finish item {} of migration {}: {}",
+                "MIGRATION", item.id, migration.id, e
+            );
+            failure.get_or_insert(e);
+        }
+        let _ = progress.send(migration.id);
+    }
+    drop(results);
+
+    match claim_error.or(failure) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Copies the image of an item into the target bucket, checks the copy and points
+/// the image at it. The item is finished here when it succeeds.
+async fn migrate_item(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    migration: &DbStorageMigration,
+    item: &DbStorageMigrationItem,
+) -> std::result::Result<(), String> {
+    let backend: StorageBackend = migration
+        .target_backend
+        .parse()
+        .map_err(|_| format!("unknown storage backend {}", migration.target_backend))?;
+    let bucket = config().LUST_BUCKET.as_str();
+
+    // The image is switched over in the same transaction that finishes the item,
+    // so a copy recorded on an unfinished item was never used.
+    if let Some(target_path) = &item.target_path {
+        compensate_upload(mm, store, bucket, target_path).await;
+        StorageMigrationDao::set_target_path(mm, &item.id, None).map_err(|e| e.to_string())?;
+    }
+
+    let image = ImageDao::get_by_id(mm, &item.image_id).map_err(|e| e.to_string())?;
+    if image.path != item.source_path {
+        return Err("image was changed since the migration was created".to_string());
+    }
+
+    let source = store
+        .get(bucket, &item.source_path, GetOptions::default())
+        .await
+        .map_err(|e| e.to_string())?;
+    let name = format!("migration-{}", item.id);
+    let file = spool_body(&name, source.body).await;
+    let res = match file {
+        Ok(file) => {
+            copy_image(
+                mm,
+                store,
+                backend,
+                &migration.target_bucket,
+                image.stored_hash,
+                &file,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    remove_staging_file(&name).await;
+    let moved = res?;
+
+    StorageMigrationDao::set_target_path(mm, &item.id, Some(moved.path.as_str()))
+        .map_err(|e| e.to_string())?;
+    let target_path = moved.path.clone();
+    let switched = StorageMigrationDao::move_image(mm, item, moved).map_err(|e| e.to_string())?;
+    if !switched {
+        compensate_upload(mm, store, bucket, &target_path).await;
+        return Err("image was changed while it was copied".to_string());
+    }
+
+    // a source left behind by a restart right here is reported by the consistency check
+    if migration.delete_source {
+        compensate_upload(mm, store, bucket, &item.source_path).await;
+    }
+
+    Ok(())
+}
+
+/// Stores the spooled source in the target bucket and reads the copy back. The copy
+/// must serve what the store said it would. Stores that re-encode (Lust) can't be held
+/// to the source bytes, their copy has to decode to the dimensions of a source that
+/// was checked against the image's stored hash. What the copy serves is recorded as
+/// the new stored hash.
+async fn copy_image(
+    mm: &ModelManager,
+    store: &dyn ImageStore,
+    backend: StorageBackend,
+    target_bucket: &str,
+    stored_hash: Option<String>,
+    file: &ScannedFile,
+) -> std::result::Result<MovedImage, String> {
+    if let Some(stored_hash) = &stored_hash {
+        if *stored_hash != file.hash {
+            return Err("source doesn't match its stored hash".to_string());
+        }
+    }
+
+    let stored = store
+        .put_file_in(backend, target_bucket, &file.path, &file.hash)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let verified = match (&stored.hash, &stored_hash) {
+        (Some(expected), _) => {
+            match fetch_object_hash(store, &config().LUST_BUCKET, &stored.image_id).await {
+                Ok(hash) if hash == *expected => Ok(hash),
+                Ok(_) => Err("copy doesn't match the source".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }
+        (None, Some(_)) => verify_reencoded(store, &stored.image_id, file).await,
+        (None, None) => Err("neither the source nor the copy can be verified".to_string()),
+    };
+
+    match verified {
+        Ok(hash) => Ok(MovedImage {
+            path: stored.image_id,
+            stored_hash: hash,
+            lust_checksum: stored.checksum,
+        }),
+        Err(e) => {
+            compensate_upload(mm, store, &config().LUST_BUCKET, &stored.image_id).await;
+            Err(e)
+        }
+    }
+}
+
+/// Checks that a re-encoded copy decodes to the source's dimensions and returns its hash.
+async fn verify_reencoded(
+    store: &dyn ImageStore,
+    image_id: &str,
+    file: &ScannedFile,
+) -> std::result::Result<String, String> {
+    let copy = store
+        .get(&config().LUST_BUCKET, image_id, GetOptions::default())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut hasher = Sha256::new();
+    let mut head = Vec::with_capacity(HEAD_LEN);
+    let mut body = copy.body.into_data_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        let missing = HEAD_LEN.saturating_sub(head.len());
+        head.extend_from_slice(&chunk[..missing.min(chunk.len())]);
+        hasher.update(&chunk);
+    }
+
+    let source = image_dimensions(file)
+        .await
+        .map_err(|_| "can't read the dimensions of the source".to_string())?;
+    let copy = blob_size(&head).map_err(|_| "can't read the dimensions of the copy".to_string())?;
+    if (copy.width, copy.height) != (source.width, source.height) {
+        return Err("copy doesn't match the source".to_string());
+    }
+    Ok(HEXLOWER.encode(&hasher.finalize()))
+}
+
+async fn spool_body(name: &str, body: Body) -> std::result::Result<ScannedFile, String> {
+    create_staging_file(name).await.map_err(|e| e.to_string())?;
+    let file = open_staging_file(name).await.map_err(|e| e.to_string())?;
+    let mut spool = Spool::new(file, staging_path(name));
+
+    let mut body = body.into_data_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| e.to_string())?;
+        spool.write(&chunk).await.map_err(|e| e.to_string())?;
+    }
+    spool.finish().await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,13 @@
+mod cleanup;
+mod import;
+mod migration;
+mod scrub;
+mod uploads;
+mod watcher;
+
+pub use cleanup::start_lust_cleanup;
+pub use import::ImportQueue;
+pub use migration::MigrationQueue;
+pub use scrub::start_scrub;
+pub use uploads::start_upload_expiry;
+pub use watcher::{start_watcher, IgnorePatterns};
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use tracing::error;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::integrity::scrub_images;
+use crate::services::store::Store;
+
+/// Periodically re-verifies a batch of stored images, so every image gets checked
+/// once every `image count / SCRUB_BATCH_SIZE` intervals. `SCRUB_INTERVAL=0` turns it off.
+pub fn start_scrub(mm: ModelManager, store: Store) {
+    if config().SCRUB_INTERVAL == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config().SCRUB_INTERVAL));
+        loop {
+            interval.tick().await;
+            if let Err(e) = scrub_images(&mm, store.as_ref(), config().SCRUB_BATCH_SIZE).await {
+                error!("{:<12} - scrub failed: {}", "SCRUB", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::upload_session::UploadSessionDao;
+use crate::ingest::staging::remove_staging_file;
+
+/// Periodically deletes resumable upload sessions nothing was uploaded to for
+/// `UPLOAD_SESSION_TTL_SECS`, together with their staging files. `0` turns it off.
+pub fn start_upload_expiry(mm: ModelManager) {
+    let ttl = config().UPLOAD_SESSION_TTL_SECS;
+    if ttl == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(ttl.min(3600)));
+        loop {
+            interval.tick().await;
+            let before = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(ttl as i64);
+            let expired = match UploadSessionDao::delete_stale(&mm, before) {
+                Ok(expired) => expired,
+                Err(e) => {
+                    error!(
+                        "{:<12} - failed to expire upload sessions: {}",
+                        "UPLOADS", e
+                    );
+                    continue;
+                }
+            };
+
+            for session_id in &expired {
+                remove_staging_file(&session_id.to_string()).await;
+            }
+            if !expired.is_empty() {
+                info!(
+                    "{:<12} - expired {} upload sessions",
+                    "UPLOADS",
+                    expired.len()
+                );
+            }
+        }
+    });
+}
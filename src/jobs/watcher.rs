@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use regex::Regex;
+use tokio::sync::mpsc;
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::db::ModelManager;
+use crate::domain::album::{AlbumDao, DbCreateAlbum};
+use crate::domain::import_job::CreateImportJob;
+use crate::domain::raw_album::list_raw_files;
+use crate::domain::storage_volume::StorageVolumeDao;
+
+use super::ImportQueue;
+
+/// File and folder names to leave alone, written as globs (`*` and `?`).
+#[derive(Clone)]
+pub struct IgnorePatterns(Vec<Regex>);
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|pattern| {
+                let pattern = regex::escape(pattern)
+                    .replace(r"\*", ".*")
+                    .replace(r"\?", ".");
+                Regex::new(&format!("^{}$", pattern)).ok()
+            })
+            .collect();
+        Self(patterns)
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.0.iter().any(|pattern| pattern.is_match(name))
+    }
+}
+
+/// Watches `IMAGES_DIR` for new folders and, once their files stop changing,
+/// creates an album for each of them and queues its import.
+pub fn start_watcher(mm: ModelManager, queue: ImportQueue) {
+    if !config().WATCH_IMAGES_DIR {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel::<PathBuf>();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) => event.paths.into_iter().for_each(|path| {
+            let _ = sender.send(path);
+        }),
+        Err(e) => error!("{:<12} - watch error: {}", "WATCHER", e),
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            error!("{:<12} - failed to start watcher: {}", "WATCHER", e);
+            return;
+        }
+    };
+
+    let root = PathBuf::from(&config().IMAGES_DIR);
+    if let Err(e) = watcher.watch(&root, RecursiveMode::Recursive) {
+        error!("{:<12} - failed to watch {:?}: {}", "WATCHER", root, e);
+        return;
+    }
+
+    info!("{:<12} - watching {:?}", "WATCHER", root);
+    tokio::spawn(run_watcher(watcher, mm, queue, root, receiver));
+}
+
+async fn run_watcher(
+    // kept alive for as long as the task runs
+    _watcher: RecommendedWatcher,
+    mm: ModelManager,
+    queue: ImportQueue,
+    root: PathBuf,
+    mut receiver: mpsc::UnboundedReceiver<PathBuf>,
+) {
+    let ignore = IgnorePatterns::new(&config().WATCH_IGNORE);
+    let settle_time = Duration::from_secs(config().WATCH_SETTLE_SECS);
+
+    // folders that were there before the watcher started are left to be imported by hand
+    let mut known = existing_folders(&root);
+    let mut pending: HashMap<String, Instant> = HashMap::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        tokio::select! {
+            path = receiver.recv() => {
+                let path = match path {
+                    Some(path) => path,
+                    None => break,
+                };
+                let folder = match top_level_folder(&root, &path) {
+                    Some(folder) => folder,
+                    None => continue,
+                };
+                if known.contains(&folder) || folder.starts_with('.') || ignore.matches(&folder) {
+                    continue;
+                }
+                debug!("{:<12} - change in {}", "WATCHER", folder);
+                pending.insert(folder, Instant::now());
+            }
+            _ = interval.tick() => {
+                let settled = pending
+                    .iter()
+                    .filter(|(_, changed_at)| changed_at.elapsed() >= settle_time)
+                    .map(|(folder, _)| folder.clone())
+                    .collect::<Vec<String>>();
+
+                for folder in settled {
+                    pending.remove(&folder);
+                    known.insert(folder.clone());
+                    if root.join(&folder).is_dir() {
+                        import_folder(&mm, &queue, &ignore, folder).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn existing_folders(root: &Path) -> HashSet<String> {
+    std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn top_level_folder(root: &Path, path: &Path) -> Option<String> {
+    match path.strip_prefix(root).ok()?.components().next()? {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    }
+}
+
+async fn import_folder(
+    mm: &ModelManager,
+    queue: &ImportQueue,
+    ignore: &IgnorePatterns,
+    folder: String,
+) {
+    match AlbumDao::get_by_title(mm, &folder) {
+        Ok(None) => (),
+        Ok(Some(_)) => {
+            info!(
+                "{:<12} - album {} already exists, skipping",
+                "WATCHER", folder
+            );
+            return;
+        }
+        Err(e) => {
+            error!(
+                "{:<12} - failed to look up album {}: {}",
+                "WATCHER", folder, e
+            );
+            return;
+        }
+    }
+
+    let volume = match StorageVolumeDao::get_default(mm) {
+        Ok(volume) => volume,
+        Err(e) => {
+            error!(
+                "{:<12} - failed to get the default volume: {}",
+                "WATCHER", e
+            );
+            return;
+        }
+    };
+    let files = match list_raw_files(&volume.mount_path, &folder).await {
+        Ok(files) => files
+            .into_iter()
+            .filter(|file| !ignore.matches(file))
+            .collect::<Vec<String>>(),
+        Err(e) => {
+            error!("{:<12} - failed to read {}: {}", "WATCHER", folder, e);
+            return;
+        }
+    };
+    if files.is_empty() {
+        return;
+    }
+
+    let album = AlbumDao::create(
+        mm,
+        DbCreateAlbum {
+            id: Uuid::new_v4(),
+            title: folder.clone(),
+            description: None,
+            original_title: folder.clone(),
+            parent_id: None,
+        },
+    );
+    let album = match album {
+        Ok(album) => album,
+        Err(e) => {
+            error!(
+                "{:<12} - failed to create album {}: {}",
+                "WATCHER", folder, e
+            );
+            return;
+        }
+    };
+
+    let job = queue.create_job(
+        mm,
+        CreateImportJob {
+            id: Uuid::new_v4(),
+            album_id: album.id,
+            album_path: folder.clone(),
+            is_primary_album: true,
+            link_duplicates: false,
+            retention: config().ORIGINALS_RETENTION.as_str().to_string(),
+            volume_id: Some(volume.id),
+        },
+        files,
+    );
+    match job {
+        Ok(job) => info!(
+            "{:<12} - queued import {} for {}",
+            "WATCHER", job.id, folder
+        ),
+        Err(e) => error!(
+            "{:<12} - failed to queue import of {}: {}",
+            "WATCHER", folder, e
+        ),
+    }
+}
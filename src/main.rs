@@ -8,6 +8,8 @@ pub mod config;
 pub mod db;
 pub mod domain;
 pub mod graphql;
+pub mod ingest;
+pub mod jobs;
 pub mod schema;
 pub mod services;
 pub mod utils;
@@ -27,10 +29,21 @@ async fn main() -> Result<()> {
     let mm = db::ModelManager::new().await?;
     mm.run_migration();
 
+    let store = services::store::build_store();
+    let import_queue = jobs::ImportQueue::start(mm.clone(), store.clone());
+    let migration_queue = jobs::MigrationQueue::start(mm.clone(), store.clone());
+    jobs::start_lust_cleanup(mm.clone(), store.clone());
+    jobs::start_scrub(mm.clone(), store.clone());
+    jobs::start_upload_expiry(mm.clone());
+    jobs::start_watcher(mm.clone(), import_queue.clone());
+
     let routes_all = Router::new()
         .merge(routes_login(mm.clone()))
-        .nest("/graphql", graphql::handler::routes(mm.clone()))
-        .nest("/api", api::api_handler::routes(mm.clone()))
+        .nest(
+            "/graphql",
+            graphql::handler::routes(mm.clone(), store.clone(), import_queue, migration_queue),
+        )
+        .nest("/api", api::api_handler::routes(mm.clone(), store))
         .route("/test", get(hello_world))
         .layer(middleware::from_fn_with_state(mm.clone(), mw_ctx_resolve))
         .layer(CookieManagerLayer::new());
@@ -10,6 +10,7 @@ diesel::table! {
         prev_image_id -> Nullable<Uuid>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        parent_id -> Nullable<Uuid>,
     }
 }
 
@@ -36,6 +37,142 @@ diesel::table! {
         is_uploaded -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        content_hash -> Nullable<Text>,
+        archive_path -> Nullable<Text>,
+        media_type -> Text,
+        paired_image_id -> Nullable<Uuid>,
+        raw_format -> Nullable<Text>,
+        volume_id -> Nullable<Uuid>,
+        lust_checksum -> Nullable<Int8>,
+        stored_hash -> Nullable<Text>,
+        integrity_status -> Text,
+        integrity_checked_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    image_metadata (image_id) {
+        image_id -> Uuid,
+        taken_at -> Nullable<Timestamp>,
+        camera_make -> Nullable<Text>,
+        camera_model -> Nullable<Text>,
+        lens_model -> Nullable<Text>,
+        exposure_time -> Nullable<Text>,
+        f_number -> Nullable<Float8>,
+        iso -> Nullable<Int4>,
+        focal_length -> Nullable<Float8>,
+        orientation -> Nullable<Int4>,
+        gps_latitude -> Nullable<Float8>,
+        gps_longitude -> Nullable<Float8>,
+        gps_altitude -> Nullable<Float8>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    import_job (id) {
+        id -> Uuid,
+        album_id -> Uuid,
+        album_path -> Text,
+        is_primary_album -> Bool,
+        link_duplicates -> Bool,
+        status -> Text,
+        total_items -> Int4,
+        processed_items -> Int4,
+        failed_items -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        order_offset -> Int4,
+        retention -> Text,
+        volume_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    import_job_item (id) {
+        id -> Uuid,
+        job_id -> Uuid,
+        file_name -> Text,
+        order_index -> Int4,
+        status -> Text,
+        error -> Nullable<Text>,
+        image_id -> Nullable<Uuid>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    lust_cleanup (id) {
+        id -> Uuid,
+        bucket -> Text,
+        lust_image_id -> Text,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    orphan_sighting (path) {
+        path -> Text,
+        first_seen_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    storage_migration (id) {
+        id -> Uuid,
+        target_backend -> Text,
+        target_bucket -> Text,
+        delete_source -> Bool,
+        status -> Text,
+        total_items -> Int4,
+        processed_items -> Int4,
+        failed_items -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    storage_migration_item (id) {
+        id -> Uuid,
+        migration_id -> Uuid,
+        image_id -> Uuid,
+        source_path -> Text,
+        target_path -> Nullable<Text>,
+        status -> Text,
+        error -> Nullable<Text>,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    storage_volume (id) {
+        id -> Uuid,
+        name -> Text,
+        mount_path -> Text,
+        capacity_bytes -> Nullable<Int8>,
+        is_read_only -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    upload_session (id) {
+        id -> Uuid,
+        album_id -> Uuid,
+        file_name -> Text,
+        upload_length -> Int8,
+        upload_offset -> Int8,
+        is_primary_album -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        link_duplicates -> Bool,
+        retention -> Text,
     }
 }
 
@@ -49,16 +186,35 @@ diesel::table! {
         is_admin -> Bool,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        avatar_path -> Nullable<Text>,
     }
 }
 
 diesel::joinable!(album -> image (prev_image_id));
 diesel::joinable!(album_image -> album (album_id));
 diesel::joinable!(album_image -> image (image_id));
+diesel::joinable!(image -> storage_volume (volume_id));
+diesel::joinable!(image_metadata -> image (image_id));
+diesel::joinable!(import_job -> album (album_id));
+diesel::joinable!(import_job_item -> image (image_id));
+diesel::joinable!(import_job -> storage_volume (volume_id));
+diesel::joinable!(import_job_item -> import_job (job_id));
+diesel::joinable!(storage_migration_item -> image (image_id));
+diesel::joinable!(storage_migration_item -> storage_migration (migration_id));
+diesel::joinable!(upload_session -> album (album_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     album,
     album_image,
     image,
+    image_metadata,
+    import_job,
+    import_job_item,
+    lust_cleanup,
+    orphan_sighting,
+    storage_migration,
+    storage_migration_item,
+    storage_volume,
+    upload_session,
     users,
 );
@@ -11,6 +11,22 @@ pub enum Error {
     LustError(LustError),
 
     UrlParseFailed(String),
+
+    NotFound(String, String),
+
+    StoreError(String),
+
+    Unsupported(String),
+}
+
+impl Error {
+    /// Whether the image is not in the store, whichever backend said so.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Error::NotFound(_, _) | Error::LustError(LustError::NotFound(_, _))
+        )
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -34,6 +50,7 @@ impl From<Error> for async_graphql::Error {
                 async_graphql::Error::new(format!("Url parse error: {}", s))
             }
             Error::LustError(e) => async_graphql::Error::new(e.to_string()),
+            e => async_graphql::Error::new(e.to_string()),
         }
     }
 }
@@ -44,6 +61,11 @@ impl ToString for Error {
             Error::ReqwestFailed(e) => format!("Reqwest error: {}", e),
             Error::UrlParseFailed(s) => format!("Url parse error: {}", s),
             Error::LustError(e) => e.to_string(),
+            Error::NotFound(bucket, image) => {
+                format!("Not found, bucket: {}, image: {}", bucket, image)
+            }
+            Error::StoreError(e) => format!("Store error: {}", e),
+            Error::Unsupported(e) => format!("Unsupported: {}", e),
         }
     }
 }
@@ -1,21 +1,28 @@
+use std::path::{Path, PathBuf};
+
 use axum::{
     body::{Body as AxumBody, Bytes},
     http::{HeaderMap, HeaderName, HeaderValue},
     response::IntoResponse,
 };
 use derive_more::Display;
+use futures_util::{stream, TryStreamExt};
 use reqwest::{
     header::{CONTENT_LENGTH, CONTENT_TYPE},
-    Body, Client, StatusCode, Url,
+    Body, StatusCode, Url,
 };
 use serde::{Deserialize, Serialize};
+use tokio::{fs::File, io::AsyncReadExt};
 use tracing::debug;
 
 use crate::config::config;
 
 use super::error::{Error, Result};
+use super::lust_client::{response_timeout, upload_timeout, LustClient};
 use super::req_client;
 
+const STREAM_CHUNK_LEN: usize = 64 * 1024;
+
 #[derive(Debug, Display, Serialize, Clone)]
 pub enum LustError {
     #[display(fmt = "Bad new image format")]
@@ -36,6 +43,15 @@ pub enum LustError {
     #[display(fmt = "Not found, bucket: {}, image: {}", _0, _1)]
     NotFound(String, String),
 
+    #[display(fmt = "Lust server error: {}", _0)]
+    ServerError(u16),
+
+    #[display(fmt = "Lust is unavailable")]
+    Unavailable,
+
+    #[display(fmt = "Lust did not answer in time")]
+    Timeout,
+
     #[display(fmt = "Undefined error")]
     Undefined,
 }
@@ -68,6 +84,9 @@ impl From<StatusCode> for LustResult {
             StatusCode::UNAUTHORIZED => LustResult::Error(LustError::Post401Unauthorized),
             StatusCode::NOT_FOUND => LustResult::Error(LustError::Post404BucketNotFound),
             StatusCode::PAYLOAD_TOO_LARGE => LustResult::Error(LustError::Post413PayloadTooLarge),
+            status if status.is_server_error() => {
+                LustResult::Error(LustError::ServerError(status.as_u16()))
+            }
             _ => LustResult::Error(LustError::Undefined),
         }
     }
@@ -97,23 +116,27 @@ impl Lust {
         req_client::build_url(&url, params)
     }
 
+    /// Uploads the file at `path` without reading it into memory.
     pub async fn post_stream(
-        client: &Client,
+        client: &LustClient,
         bucket: &str,
+        path: &Path,
         size: u64,
-        file: Bytes,
     ) -> Result<LustResponse> {
-        let url = Self::build_post_url(bucket, None)?;
-        debug!("{:<12} - LUST creating file", "LUST");
-        let body = Body::from(file);
+        let url = Self::build_post_url(
+            bucket,
+            Some(vec![("format".to_string(), "jpeg".to_string())]),
+        )?;
+        debug!("{:<12} - LUST streaming file - {}", "LUST", path.display());
         let res = client
-            .post(url)
-            .header(CONTENT_TYPE, "application/octet-stream")
-            .header(CONTENT_LENGTH, size.to_string())
-            .body(body)
-            .send()
-            .await
-            .map_err(|e| e.into());
+            .send(false, upload_timeout(size), |client| {
+                client
+                    .post(url.clone())
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .header(CONTENT_LENGTH, size.to_string())
+                    .body(file_body(path.to_path_buf()))
+            })
+            .await;
 
         match res {
             Ok(res) => match res.status().into() {
@@ -133,22 +156,31 @@ impl Lust {
         }
     }
 
-    pub async fn post_file(client: &Client, bucket: &str, file: Vec<u8>) -> Result<LustResponse> {
+    pub async fn post_file(
+        client: &LustClient,
+        bucket: &str,
+        file: Vec<u8>,
+    ) -> Result<LustResponse> {
         let url = Self::build_post_url(
             bucket,
             Some(vec![("format".to_string(), "jpeg".to_string())]),
         )?;
-        debug!("{:<12} - LUST creating file", "LUST");
         let size = file.len();
-        println!("url: {}, size: {}", url, size);
+        debug!(
+            "{:<12} - LUST creating file - {}, size: {}",
+            "LUST", url, size
+        );
+        // cheap to clone for retries
+        let file = Bytes::from(file);
         let res = client
-            .post(url)
-            .header(CONTENT_TYPE, "application/octet-stream")
-            .header(CONTENT_LENGTH, size.to_string())
-            .body(file)
-            .send()
-            .await
-            .map_err(|e| e.into());
+            .send(false, upload_timeout(size as u64), |client| {
+                client
+                    .post(url.clone())
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .header(CONTENT_LENGTH, size.to_string())
+                    .body(Body::from(file.clone()))
+            })
+            .await;
 
         match res {
             Ok(res) => match res.status().into() {
@@ -169,18 +201,16 @@ impl Lust {
     }
 
     pub async fn get_file(
-        client: &Client,
+        client: &LustClient,
         bucket: &str,
         image_id: &str,
         params: Option<Vec<(String, String)>>,
-    ) -> Result<(impl IntoResponse, HeaderMap)> {
+    ) -> Result<(AxumBody, HeaderMap)> {
         let url = Self::build_get_url(bucket, params, image_id)?;
         debug!("{:<12} - LUST getting file - {}", "LUST", &url);
         let res = client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| Error::ReqwestFailed(e.to_string()))?;
+            .send(true, response_timeout(), |client| client.get(url.clone()))
+            .await?;
 
         match res.status() {
             StatusCode::OK => {
@@ -213,6 +243,37 @@ impl Lust {
             StatusCode::BAD_REQUEST => {
                 Err(LustError::BadRequest(bucket.to_string(), image_id.to_string()).into())
             }
+            status if status.is_server_error() => {
+                Err(LustError::ServerError(status.as_u16()).into())
+            }
+            _ => {
+                debug!(
+                    "{:<12} - LUST undefined error: {} for image: {}",
+                    "LUST",
+                    res.status(),
+                    image_id
+                );
+                Err(LustError::Undefined.into())
+            }
+        }
+    }
+
+    pub async fn file_exists(client: &LustClient, bucket: &str, image_id: &str) -> Result<bool> {
+        let url = Self::build_get_url(bucket, None, image_id)?;
+        debug!("{:<12} - LUST checking file - {}", "LUST", &url);
+        let res = client
+            .send(true, response_timeout(), |client| client.get(url.clone()))
+            .await?;
+
+        match res.status() {
+            StatusCode::OK => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            StatusCode::BAD_REQUEST => {
+                Err(LustError::BadRequest(bucket.to_string(), image_id.to_string()).into())
+            }
+            status if status.is_server_error() => {
+                Err(LustError::ServerError(status.as_u16()).into())
+            }
             _ => {
                 debug!(
                     "{:<12} - LUST undefined error: {} for image: {}",
@@ -225,14 +286,14 @@ impl Lust {
         }
     }
 
-    pub async fn delete_file(client: &Client, bucket: &str, image_id: &str) -> Result<()> {
+    pub async fn delete_file(client: &LustClient, bucket: &str, image_id: &str) -> Result<()> {
         let url = Self::build_get_url(bucket, None, &image_id)?;
         debug!("{:<12} - LUST deleting file", "LUST");
         let res = client
-            .delete(url)
-            .send()
-            .await
-            .map_err(|e| Error::ReqwestFailed(e.to_string()))?;
+            .send(true, response_timeout(), |client| {
+                client.delete(url.clone())
+            })
+            .await?;
 
         match res.status() {
             StatusCode::OK => {
@@ -245,6 +306,9 @@ impl Lust {
             StatusCode::BAD_REQUEST => {
                 Err(LustError::BadRequest(bucket.to_string(), image_id.to_string()).into())
             }
+            status if status.is_server_error() => {
+                Err(LustError::ServerError(status.as_u16()).into())
+            }
             _ => {
                 debug!(
                     "{:<12} - LUST undefined error: {} for image: {}",
@@ -257,3 +321,22 @@ impl Lust {
         }
     }
 }
+
+/// Streams the file in chunks. It is only opened once the body is polled,
+/// so every retry reads it from the start.
+fn file_body(path: PathBuf) -> Body {
+    let chunks = stream::once(File::open(path))
+        .map_ok(|file| {
+            stream::try_unfold(file, |mut file| async move {
+                let mut chunk = vec![0; STREAM_CHUNK_LEN];
+                let read = file.read(&mut chunk).await?;
+                if read == 0 {
+                    return Ok(None);
+                }
+                chunk.truncate(read);
+                Ok::<_, std::io::Error>(Some((Bytes::from(chunk), file)))
+            })
+        })
+        .try_flatten();
+    Body::wrap_stream(chunks)
+}
@@ -0,0 +1,215 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use crate::config::config;
+
+use super::error::Result;
+use super::lust::LustError;
+
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests go through.
+    Closed,
+    /// Lust is considered down, requests fail right away.
+    Open,
+    /// The cooldown is over, one trial request decides whether to close again.
+    HalfOpen,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct LustHealth {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    trial_in_flight: bool,
+    last_error: Option<String>,
+}
+
+/// HTTP client for Lust with timeouts, retries of idempotent requests with jittered
+/// exponential backoff and a circuit breaker that fails fast while Lust is down.
+///
+/// Only the time until Lust answers is limited, bodies Lust sends back are streamed
+/// to clients for as long as they take.
+pub struct LustClient {
+    client: Client,
+    breaker: Mutex<Breaker>,
+}
+
+impl LustClient {
+    pub fn from_config() -> Self {
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(config().LUST_CONNECT_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|e| panic!("failed to build the Lust client: {}", e));
+
+        Self {
+            client,
+            breaker: Mutex::new(Breaker {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+                last_error: None,
+            }),
+        }
+    }
+
+    pub fn health(&self) -> LustHealth {
+        let breaker = self.breaker.lock().unwrap();
+        LustHealth {
+            state: state_of(&breaker),
+            consecutive_failures: breaker.consecutive_failures,
+            last_error: breaker.last_error.clone(),
+        }
+    }
+
+    /// Sends the request built by `request`, building it again for every retry.
+    /// `timeout` is how long sending the request body and waiting for the response
+    /// headers may take, see `response_timeout` and `upload_timeout`.
+    ///
+    /// Requests that aren't `idempotent` are only retried when Lust turned them away
+    /// without handling them: they never reached it, or a proxy or an overloaded Lust
+    /// answered 502 or 503. Any other failure of an upload may have stored the image,
+    /// a retry could store it twice.
+    /// Server errors come back as the response, for the caller to map.
+    pub async fn send<F>(&self, idempotent: bool, timeout: Duration, request: F) -> Result<Response>
+    where
+        F: Fn(&Client) -> RequestBuilder,
+    {
+        let _permit = self.acquire()?;
+
+        let mut attempt = 0;
+        loop {
+            let sent = tokio::time::timeout(timeout, request(&self.client).send()).await;
+            let (failure, rejected, res) = match sent {
+                Ok(Ok(res)) if res.status().is_server_error() => {
+                    let rejected = matches!(
+                        res.status(),
+                        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE
+                    );
+                    (res.status().to_string(), rejected, Ok(res))
+                }
+                Ok(Ok(res)) => {
+                    self.record_success();
+                    return Ok(res);
+                }
+                Ok(Err(e)) if e.is_connect() => (e.to_string(), true, Err(e.into())),
+                Ok(Err(e)) if e.is_timeout() => (e.to_string(), false, Err(e.into())),
+                // e.g. a request that couldn't be built, nothing that says Lust is down
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_) => {
+                    let e = LustError::Timeout;
+                    (e.to_string(), false, Err(e.into()))
+                }
+            };
+
+            if attempt >= config().LUST_MAX_RETRIES || !(idempotent || rejected) {
+                self.record_failure(failure);
+                return res;
+            }
+
+            attempt += 1;
+            let delay = backoff(attempt);
+            debug!(
+                "{:<12} - attempt {} failed: {}, retrying in {:?}",
+                "LUST", attempt, failure, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn acquire(&self) -> Result<Permit<'_>> {
+        let mut breaker = self.breaker.lock().unwrap();
+        match state_of(&breaker) {
+            CircuitState::Closed => Ok(Permit {
+                breaker: &self.breaker,
+                trial: false,
+            }),
+            CircuitState::HalfOpen if !breaker.trial_in_flight => {
+                breaker.trial_in_flight = true;
+                Ok(Permit {
+                    breaker: &self.breaker,
+                    trial: true,
+                })
+            }
+            _ => Err(LustError::Unavailable.into()),
+        }
+    }
+
+    fn record_success(&self) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if breaker.open_until.is_some() {
+            warn!("{:<12} - Lust is reachable again", "LUST");
+        }
+        breaker.consecutive_failures = 0;
+        breaker.open_until = None;
+    }
+
+    fn record_failure(&self, error: String) {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.consecutive_failures += 1;
+        breaker.last_error = Some(error);
+
+        if breaker.consecutive_failures >= config().LUST_BREAKER_THRESHOLD {
+            let cooldown = Duration::from_secs(config().LUST_BREAKER_COOLDOWN_SECS);
+            warn!(
+                "{:<12} - {} failures in a row, pausing requests for {:?}",
+                "LUST", breaker.consecutive_failures, cooldown
+            );
+            breaker.open_until = Some(Instant::now() + cooldown);
+        }
+    }
+}
+
+/// Lets a request through the breaker. A half-open trial ends when its permit is dropped,
+/// also when the request is cancelled halfway, so the breaker never waits on it forever.
+struct Permit<'a> {
+    breaker: &'a Mutex<Breaker>,
+    trial: bool,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.trial {
+            self.breaker.lock().unwrap().trial_in_flight = false;
+        }
+    }
+}
+
+/// For requests without a body worth mentioning.
+pub fn response_timeout() -> Duration {
+    Duration::from_secs(config().LUST_RESPONSE_TIMEOUT_SECS)
+}
+
+/// For uploads, which get as long as `size` bytes take at `LUST_UPLOAD_MIN_KBPS` on top.
+pub fn upload_timeout(size: u64) -> Duration {
+    let rate = config().LUST_UPLOAD_MIN_KBPS.max(1) * 1024;
+    response_timeout() + Duration::from_secs(size / rate)
+}
+
+fn state_of(breaker: &Breaker) -> CircuitState {
+    match breaker.open_until {
+        None => CircuitState::Closed,
+        Some(until) if Instant::now() < until => CircuitState::Open,
+        Some(_) => CircuitState::HalfOpen,
+    }
+}
+
+/// Full jitter: a random delay up to the exponential backoff for the attempt.
+fn backoff(attempt: u32) -> Duration {
+    let max = config()
+        .LUST_RETRY_BASE_MS
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(config().LUST_RETRY_MAX_MS);
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max))
+}
@@ -1,3 +1,5 @@
 pub mod error;
 pub mod lust;
+pub mod lust_client;
 pub mod req_client;
+pub mod store;
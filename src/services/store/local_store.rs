@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::services::error::{Error, Result};
+
+use super::render::{original_file, Rendition, ORIGINAL};
+use super::{object_hash, GetOptions, ImageStore, StorageBackend, StoredFile, StoredImage};
+
+/// Keeps images on the local filesystem and renders the sizing presets itself,
+/// caching every rendered variant next to the original:
+/// `{root}/{bucket}/{image_id}/original` and `{root}/{bucket}/{image_id}/{size}.{format}`.
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: PathBuf::from(root),
+        }
+    }
+
+    fn image_dir(&self, bucket: &str, image_id: &str) -> Result<PathBuf> {
+        // ids are generated here, anything else must not reach the filesystem
+        let image_id = Uuid::parse_str(image_id)
+            .map_err(|_| Error::NotFound(bucket.to_string(), image_id.to_string()))?;
+        Ok(self.root.join(bucket).join(image_id.to_string()))
+    }
+}
+
+#[async_trait]
+impl ImageStore for LocalStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Local
+    }
+
+    async fn put(&self, bucket: &str, bytes: Vec<u8>) -> Result<StoredImage> {
+        image::guess_format(&bytes).map_err(|e| Error::StoreError(e.to_string()))?;
+
+        let image_id = Uuid::new_v4().to_string();
+        let hash = object_hash(&bytes);
+        let dir = self.image_dir(bucket, &image_id)?;
+        fs::create_dir_all(&dir).await.map_err(store_error)?;
+        fs::write(dir.join(ORIGINAL), bytes)
+            .await
+            .map_err(store_error)?;
+
+        debug!("{:<12} - stored {} in {}", "LOCAL_STORE", image_id, bucket);
+        Ok(StoredImage {
+            image_id,
+            checksum: None,
+            hash: Some(hash),
+        })
+    }
+
+    async fn put_file(&self, bucket: &str, path: &Path, hash: &str) -> Result<StoredImage> {
+        let image_id = Uuid::new_v4().to_string();
+        let dir = self.image_dir(bucket, &image_id)?;
+        fs::create_dir_all(&dir).await.map_err(store_error)?;
+        fs::copy(path, dir.join(ORIGINAL))
+            .await
+            .map_err(store_error)?;
+
+        debug!("{:<12} - stored {} in {}", "LOCAL_STORE", image_id, bucket);
+        Ok(StoredImage {
+            image_id,
+            checksum: None,
+            hash: Some(hash.to_string()),
+        })
+    }
+
+    async fn get(&self, bucket: &str, image_id: &str, options: GetOptions) -> Result<StoredFile> {
+        let dir = self.image_dir(bucket, image_id)?;
+        let not_found = || Error::NotFound(bucket.to_string(), image_id.to_string());
+
+        let rendition = match Rendition::from_options(options)? {
+            Some(rendition) => rendition,
+            None => {
+                let original = fs::read(dir.join(ORIGINAL))
+                    .await
+                    .map_err(|_| not_found())?;
+                return Ok(original_file(original));
+            }
+        };
+
+        let variant = dir.join(rendition.name());
+        let bytes = match fs::read(&variant).await {
+            Ok(bytes) => bytes,
+            Err(_) => {
+                let original = fs::read(dir.join(ORIGINAL))
+                    .await
+                    .map_err(|_| not_found())?;
+                let bytes = rendition.render(original).await?;
+                fs::write(&variant, &bytes).await.map_err(store_error)?;
+                bytes
+            }
+        };
+        Ok(rendition.into_file(bytes))
+    }
+
+    async fn delete(&self, bucket: &str, image_id: &str) -> Result<()> {
+        let dir = self.image_dir(bucket, image_id)?;
+        if !is_dir(&dir).await {
+            return Err(Error::NotFound(bucket.to_string(), image_id.to_string()));
+        }
+        fs::remove_dir_all(dir).await.map_err(store_error)
+    }
+
+    async fn exists(&self, bucket: &str, image_id: &str) -> Result<bool> {
+        let dir = self.image_dir(bucket, image_id)?;
+        Ok(fs::metadata(dir.join(ORIGINAL)).await.is_ok())
+    }
+
+    async fn list(&self, bucket: &str) -> Result<Vec<String>> {
+        let dir = self.root.join(bucket);
+        if !is_dir(&dir).await {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(dir).await.map_err(store_error)?;
+        let mut images = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(store_error)? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if Uuid::parse_str(&name).is_ok() {
+                images.push(name);
+            }
+        }
+        images.sort();
+        Ok(images)
+    }
+}
+
+fn store_error(e: std::io::Error) -> Error {
+    Error::StoreError(e.to_string())
+}
+
+async fn is_dir(path: &Path) -> bool {
+    fs::metadata(path)
+        .await
+        .map(|meta| meta.is_dir())
+        .unwrap_or(false)
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs::{metadata, read_dir};
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::services::error::{Error, Result};
+use crate::services::lust::Lust;
+use crate::services::lust_client::{LustClient, LustHealth};
+
+use super::{GetOptions, ImageStore, StorageBackend, StoredFile, StoredImage};
+
+pub struct LustStore {
+    client: LustClient,
+}
+
+impl LustStore {
+    pub fn new(client: LustClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ImageStore for LustStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Lust
+    }
+
+    fn lust_health(&self) -> Option<LustHealth> {
+        Some(self.client.health())
+    }
+
+    async fn put(&self, bucket: &str, bytes: Vec<u8>) -> Result<StoredImage> {
+        let response = Lust::post_file(&self.client, bucket, bytes).await?;
+        Ok(StoredImage {
+            image_id: response.image_id,
+            checksum: Some(response.checksum),
+            // Lust re-encodes uploads, what it serves is only known after fetching it
+            hash: None,
+        })
+    }
+
+    async fn put_file(&self, bucket: &str, path: &Path, _hash: &str) -> Result<StoredImage> {
+        let size = metadata(path)
+            .await
+            .map_err(|e| Error::StoreError(e.to_string()))?
+            .len();
+        let response = Lust::post_stream(&self.client, bucket, path, size).await?;
+        Ok(StoredImage {
+            image_id: response.image_id,
+            checksum: Some(response.checksum),
+            hash: None,
+        })
+    }
+
+    async fn get(&self, bucket: &str, image_id: &str, options: GetOptions) -> Result<StoredFile> {
+        let params = [("size", options.size), ("format", options.format)]
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (name.to_string(), value)))
+            .collect::<Vec<(String, String)>>();
+
+        let (body, headers) = Lust::get_file(&self.client, bucket, image_id, Some(params)).await?;
+        Ok(StoredFile { body, headers })
+    }
+
+    async fn delete(&self, bucket: &str, image_id: &str) -> Result<()> {
+        Lust::delete_file(&self.client, bucket, image_id).await
+    }
+
+    async fn exists(&self, bucket: &str, image_id: &str) -> Result<bool> {
+        Lust::file_exists(&self.client, bucket, image_id).await
+    }
+
+    /// Lust has no listing endpoint, so this walks its storage directory when it is mounted.
+    /// Lust stores each image under a directory or file named after its id.
+    async fn list(&self, _bucket: &str) -> Result<Vec<String>> {
+        let dir = config().LUST_STORAGE_DIR.as_ref().ok_or_else(|| {
+            Error::Unsupported("listing Lust images requires LUST_STORAGE_DIR".to_string())
+        })?;
+
+        let mut found = HashSet::new();
+        let mut dirs = vec![PathBuf::from(dir)];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = read_dir(&dir)
+                .await
+                .map_err(|e| Error::StoreError(e.to_string()))?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| Error::StoreError(e.to_string()))?
+            {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // hidden entries hold our own staging and archive files
+                if name.starts_with('.') {
+                    continue;
+                }
+                let path = entry.path();
+                let stem = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if Uuid::parse_str(&stem).is_ok() {
+                    found.insert(stem);
+                }
+                let is_dir = entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false);
+                if is_dir {
+                    dirs.push(path);
+                }
+            }
+        }
+
+        let mut found = found.into_iter().collect::<Vec<String>>();
+        found.sort();
+        Ok(found)
+    }
+}
@@ -0,0 +1,159 @@
+use std::{path::Path, str::FromStr, sync::Arc};
+
+use async_trait::async_trait;
+use axum::{body::Body, http::HeaderMap};
+use data_encoding::HEXLOWER;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+
+use crate::config::config;
+
+use super::error::{Error, Result};
+use super::lust_client::{LustClient, LustHealth};
+
+mod local_store;
+mod lust_store;
+mod render;
+mod router;
+mod s3_store;
+
+pub use local_store::LocalStore;
+pub use lust_store::LustStore;
+pub use router::{decode_location, encode_path, StoreRouter};
+pub use s3_store::S3Store;
+
+pub type Store = Arc<dyn ImageStore>;
+
+/// Where image files are kept, chosen with `STORAGE_BACKEND`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    #[default]
+    Lust,
+    Local,
+    S3,
+}
+
+impl StorageBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageBackend::Lust => "lust",
+            StorageBackend::Local => "local",
+            StorageBackend::S3 => "s3",
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "lust" => Ok(StorageBackend::Lust),
+            "local" => Ok(StorageBackend::Local),
+            "s3" => Ok(StorageBackend::S3),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct StoredImage {
+    /// Id the image is fetched and deleted with, kept in `image.path`.
+    /// Prefixed with the backend when it comes from the `StoreRouter`.
+    pub image_id: String,
+    /// Checksum the store reported for the upload, only Lust has one.
+    pub checksum: Option<i64>,
+    /// `object_hash` of what `get` will serve, for stores that serve the bytes they were given.
+    pub hash: Option<String>,
+}
+
+#[derive(Default)]
+pub struct GetOptions {
+    /// Name of a sizing preset, e.g. `small`.
+    pub size: Option<String>,
+    /// Output format, e.g. `jpeg` or `webp`.
+    pub format: Option<String>,
+}
+
+pub struct StoredFile {
+    pub body: Body,
+    pub headers: HeaderMap,
+}
+
+#[async_trait]
+pub trait ImageStore: Send + Sync {
+    fn backend(&self) -> StorageBackend;
+
+    /// Circuit breaker state of the Lust client, when Lust is configured.
+    fn lust_health(&self) -> Option<LustHealth> {
+        None
+    }
+
+    async fn put(&self, bucket: &str, bytes: Vec<u8>) -> Result<StoredImage>;
+
+    /// Stores a file without loading it into memory.
+    /// `hash` is its `object_hash`, which the caller worked out while reading it in.
+    async fn put_file(&self, bucket: &str, path: &Path, hash: &str) -> Result<StoredImage>;
+
+    /// Like `put_file`, but into `backend` rather than the default one. The returned id
+    /// records `bucket`, so the image is found there whatever bucket it is asked for with.
+    async fn put_file_in(
+        &self,
+        backend: StorageBackend,
+        _bucket: &str,
+        _path: &Path,
+        _hash: &str,
+    ) -> Result<StoredImage> {
+        Err(Error::Unsupported(format!(
+            "{} storage can't store into {}",
+            self.backend().as_str(),
+            backend.as_str()
+        )))
+    }
+
+    async fn get(&self, bucket: &str, image_id: &str, options: GetOptions) -> Result<StoredFile>;
+
+    async fn delete(&self, bucket: &str, image_id: &str) -> Result<()>;
+
+    async fn exists(&self, bucket: &str, image_id: &str) -> Result<bool>;
+
+    /// Ids of every image the backend holds for the bucket.
+    async fn list(&self, bucket: &str) -> Result<Vec<String>>;
+}
+
+/// Sets up every backend that is configured, so images stored before
+/// `STORAGE_BACKEND` changed can still be served.
+pub fn build_store() -> Store {
+    let mut stores: Vec<Box<dyn ImageStore>> =
+        vec![Box::new(LocalStore::new(&config().LOCAL_STORE_DIR))];
+    if !config().LUST_URL.is_empty() {
+        stores.push(Box::new(LustStore::new(LustClient::from_config())));
+    }
+    if let Some(endpoint) = &config().S3_ENDPOINT {
+        let s3 = S3Store::new(endpoint)
+            .unwrap_or_else(|e| panic!("failed to set up S3 storage: {}", e.to_string()));
+        stores.push(Box::new(s3));
+    }
+
+    Arc::new(StoreRouter::new(config().STORAGE_BACKEND, stores))
+}
+
+/// Hash stored objects are verified with when they are scrubbed.
+pub fn object_hash(bytes: &[u8]) -> String {
+    HEXLOWER.encode(&Sha256::digest(bytes))
+}
+
+/// `object_hash` of what the store serves for the image, hashed as it streams in.
+pub async fn fetch_object_hash(
+    store: &dyn ImageStore,
+    bucket: &str,
+    image_id: &str,
+) -> Result<String> {
+    let stored = store.get(bucket, image_id, GetOptions::default()).await?;
+
+    let mut hasher = Sha256::new();
+    let mut body = stored.body.into_data_stream();
+    while let Some(chunk) = body.next().await {
+        hasher.update(chunk.map_err(|e| Error::StoreError(e.to_string()))?);
+    }
+    Ok(HEXLOWER.encode(&hasher.finalize()))
+}
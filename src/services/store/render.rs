@@ -0,0 +1,134 @@
+use std::io::Cursor;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue},
+};
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageOutputFormat};
+
+use crate::services::error::{Error, Result};
+
+use super::{GetOptions, StoredFile};
+
+pub const ORIGINAL: &str = "original";
+const JPEG_QUALITY: u8 = 85;
+
+/// Same presets as the Lust buckets in `lust_config/config.yaml`.
+const PRESETS: [(&str, u32); 3] = [("small", 600), ("medium", 1600), ("large", 3200)];
+
+/// A resized and/or re-encoded variant of an original, for the stores
+/// that render the presets themselves.
+pub struct Rendition {
+    max_side: Option<u32>,
+    format: ImageFormat,
+}
+
+impl Rendition {
+    /// `None` when the original is asked for as it is.
+    pub fn from_options(options: GetOptions) -> Result<Option<Self>> {
+        if options.size.is_none() && options.format.is_none() {
+            return Ok(None);
+        }
+
+        let max_side = match options.size {
+            Some(size) => Some(
+                preset_size(&size)
+                    .ok_or_else(|| Error::Unsupported(format!("unknown size preset: {}", size)))?,
+            ),
+            None => None,
+        };
+        let format = match options.format {
+            Some(format) => output_format(&format)
+                .ok_or_else(|| Error::Unsupported(format!("unsupported format: {}", format)))?,
+            None => ImageFormat::Jpeg,
+        };
+
+        Ok(Some(Self { max_side, format }))
+    }
+
+    /// Name the rendition is cached under, next to the original.
+    pub fn name(&self) -> String {
+        let extension = self
+            .format
+            .extensions_str()
+            .first()
+            .copied()
+            .unwrap_or("bin");
+        match self.max_side {
+            Some(max_side) => format!("{}.{}", max_side, extension),
+            None => format!("{}.{}", ORIGINAL, extension),
+        }
+    }
+
+    pub async fn render(&self, original: Vec<u8>) -> Result<Vec<u8>> {
+        let max_side = self.max_side;
+        let format = self.format;
+        tokio::task::spawn_blocking(move || render(&original, max_side, format))
+            .await
+            .map_err(|e| Error::StoreError(e.to_string()))?
+    }
+
+    pub fn into_file(self, bytes: Vec<u8>) -> StoredFile {
+        stored_file(bytes, self.format)
+    }
+}
+
+pub fn original_file(bytes: Vec<u8>) -> StoredFile {
+    let format = image::guess_format(&bytes).unwrap_or(ImageFormat::Jpeg);
+    stored_file(bytes, format)
+}
+
+fn stored_file(bytes: Vec<u8>, format: ImageFormat) -> StoredFile {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(format.to_mime_type()),
+    );
+    StoredFile {
+        body: Body::from(bytes),
+        headers,
+    }
+}
+
+fn preset_size(name: &str) -> Option<u32> {
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, size)| *size)
+}
+
+fn output_format(name: &str) -> Option<ImageFormat> {
+    match name {
+        "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+        "png" => Some(ImageFormat::Png),
+        "webp" => Some(ImageFormat::WebP),
+        _ => None,
+    }
+}
+
+/// Scales the image down to fit `max_side` (never up) and encodes it.
+fn render(original: &[u8], max_side: Option<u32>, format: ImageFormat) -> Result<Vec<u8>> {
+    let mut image =
+        image::load_from_memory(original).map_err(|e| Error::StoreError(e.to_string()))?;
+    if let Some(max_side) = max_side {
+        if image.width() > max_side || image.height() > max_side {
+            image = image.resize(max_side, max_side, FilterType::Lanczos3);
+        }
+    }
+
+    let output = match format {
+        ImageFormat::Png => ImageOutputFormat::Png,
+        ImageFormat::WebP => ImageOutputFormat::WebP,
+        _ => {
+            // jpeg has no alpha channel
+            image = DynamicImage::ImageRgb8(image.to_rgb8());
+            ImageOutputFormat::Jpeg(JPEG_QUALITY)
+        }
+    };
+
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), output)
+        .map_err(|e| Error::StoreError(e.to_string()))?;
+    Ok(bytes)
+}
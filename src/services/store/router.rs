@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use crate::services::error::{Error, Result};
+use crate::services::lust_client::LustHealth;
+
+use super::{GetOptions, ImageStore, StorageBackend, StoredFile, StoredImage};
+
+/// Sends every call to the backend the image lives in, so images stay readable
+/// after `STORAGE_BACKEND` changes. New images go to the configured backend.
+///
+/// `image.path` records the backend as `{backend}:{key}`. Lust keys carry no prefix,
+/// which keeps paths from before there was more than one backend valid.
+/// Images moved by a storage migration also record their bucket, `{backend}@{bucket}:{key}`,
+/// which wins over the bucket they are asked for with.
+pub struct StoreRouter {
+    default: StorageBackend,
+    stores: Vec<Box<dyn ImageStore>>,
+}
+
+impl StoreRouter {
+    pub fn new(default: StorageBackend, stores: Vec<Box<dyn ImageStore>>) -> Self {
+        Self { default, stores }
+    }
+
+    fn store(&self, backend: StorageBackend) -> Result<&dyn ImageStore> {
+        self.stores
+            .iter()
+            .find(|store| store.backend() == backend)
+            .map(|store| store.as_ref())
+            .ok_or_else(|| {
+                Error::Unsupported(format!("{} storage is not configured", backend.as_str()))
+            })
+    }
+
+    fn resolve<'a>(&self, path: &'a str) -> Result<(&dyn ImageStore, Option<&'a str>, &'a str)> {
+        let (backend, bucket, key) = match decode_location(path) {
+            // unprefixed paths written by the local store before Lust was optional
+            (StorageBackend::Lust, None, key) if self.store(StorageBackend::Lust).is_err() => {
+                (self.default, None, key)
+            }
+            decoded => decoded,
+        };
+        Ok((self.store(backend)?, bucket, key))
+    }
+}
+
+pub fn encode_path(backend: StorageBackend, key: &str) -> String {
+    match backend {
+        StorageBackend::Lust => key.to_string(),
+        backend => format!("{}:{}", backend.as_str(), key),
+    }
+}
+
+pub fn encode_path_in(backend: StorageBackend, bucket: &str, key: &str) -> String {
+    format!("{}@{}:{}", backend.as_str(), bucket, key)
+}
+
+/// Backend, bucket (when the path records one) and key of a path.
+pub fn decode_location(path: &str) -> (StorageBackend, Option<&str>, &str) {
+    let decoded = path.split_once(':').and_then(|(prefix, key)| {
+        let (backend, bucket) = match prefix.split_once('@') {
+            Some((backend, bucket)) => (backend, Some(bucket)),
+            None => (prefix, None),
+        };
+        backend.parse().ok().map(|backend| (backend, bucket, key))
+    });
+    decoded.unwrap_or((StorageBackend::Lust, None, path))
+}
+
+#[async_trait]
+impl ImageStore for StoreRouter {
+    fn backend(&self) -> StorageBackend {
+        self.default
+    }
+
+    fn lust_health(&self) -> Option<LustHealth> {
+        self.store(StorageBackend::Lust).ok()?.lust_health()
+    }
+
+    async fn put(&self, bucket: &str, bytes: Vec<u8>) -> Result<StoredImage> {
+        let stored = self.store(self.default)?.put(bucket, bytes).await?;
+        Ok(StoredImage {
+            image_id: encode_path(self.default, &stored.image_id),
+            ..stored
+        })
+    }
+
+    async fn put_file(&self, bucket: &str, path: &Path, hash: &str) -> Result<StoredImage> {
+        let stored = self
+            .store(self.default)?
+            .put_file(bucket, path, hash)
+            .await?;
+        Ok(StoredImage {
+            image_id: encode_path(self.default, &stored.image_id),
+            ..stored
+        })
+    }
+
+    async fn put_file_in(
+        &self,
+        backend: StorageBackend,
+        bucket: &str,
+        path: &Path,
+        hash: &str,
+    ) -> Result<StoredImage> {
+        let stored = self.store(backend)?.put_file(bucket, path, hash).await?;
+        Ok(StoredImage {
+            image_id: encode_path_in(backend, bucket, &stored.image_id),
+            ..stored
+        })
+    }
+
+    async fn get(&self, bucket: &str, image_id: &str, options: GetOptions) -> Result<StoredFile> {
+        let (store, recorded, key) = self.resolve(image_id)?;
+        store.get(recorded.unwrap_or(bucket), key, options).await
+    }
+
+    async fn delete(&self, bucket: &str, image_id: &str) -> Result<()> {
+        let (store, recorded, key) = self.resolve(image_id)?;
+        store.delete(recorded.unwrap_or(bucket), key).await
+    }
+
+    async fn exists(&self, bucket: &str, image_id: &str) -> Result<bool> {
+        let (store, recorded, key) = self.resolve(image_id)?;
+        store.exists(recorded.unwrap_or(bucket), key).await
+    }
+
+    /// Images of all backends that can list them.
+    async fn list(&self, bucket: &str) -> Result<Vec<String>> {
+        let mut images = Vec::new();
+        let mut unsupported = Vec::new();
+        for store in &self.stores {
+            match store.list(bucket).await {
+                Ok(keys) => images.extend(
+                    keys.into_iter()
+                        .map(|key| encode_path(store.backend(), &key)),
+                ),
+                Err(Error::Unsupported(e)) => unsupported.push(e),
+                Err(e) => return Err(e),
+            }
+        }
+        if unsupported.len() == self.stores.len() {
+            return Err(Error::Unsupported(unsupported.join(", ")));
+        }
+
+        images.sort();
+        Ok(images)
+    }
+}
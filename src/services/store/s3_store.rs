@@ -0,0 +1,220 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use s3::{creds::Credentials, error::S3Error, request::ResponseData, Bucket, Region};
+use tokio::fs;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::services::error::{Error, Result};
+
+use super::render::{original_file, Rendition, ORIGINAL};
+use super::{object_hash, GetOptions, ImageStore, StorageBackend, StoredFile, StoredImage};
+
+/// Keeps images in an S3-compatible object store (AWS, MinIO, ...) with the same layout
+/// as the local store: `{bucket}/{image_id}/original` next to its rendered variants.
+/// Variants are only written back when `S3_STORE_RENDITIONS` is set.
+pub struct S3Store {
+    bucket: Bucket,
+    store_renditions: bool,
+}
+
+impl S3Store {
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let region = Region::Custom {
+            region: config().S3_REGION.clone(),
+            endpoint: endpoint.to_string(),
+        };
+        let credentials = Credentials::new(
+            config().S3_ACCESS_KEY.as_deref(),
+            config().S3_SECRET_KEY.as_deref(),
+            None,
+            None,
+            None,
+        )
+        .map_err(|e| Error::StoreError(e.to_string()))?;
+
+        let mut bucket = Bucket::new(&config().S3_BUCKET, region, credentials).map_err(s3_error)?;
+        if config().S3_PATH_STYLE {
+            // MinIO and most self hosted stores don't serve buckets as subdomains
+            bucket = bucket.with_path_style();
+        }
+
+        Ok(Self {
+            bucket,
+            store_renditions: config().S3_STORE_RENDITIONS,
+        })
+    }
+
+    fn image_prefix(&self, bucket: &str, image_id: &str) -> Result<String> {
+        let image_id = Uuid::parse_str(image_id)
+            .map_err(|_| Error::NotFound(bucket.to_string(), image_id.to_string()))?;
+        Ok(format!("{}/{}/", bucket, image_id))
+    }
+
+    /// `None` when the object doesn't exist.
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.bucket.get_object(key).await.map_err(s3_error)?;
+        match response.status_code() {
+            404 => Ok(None),
+            _ => check_status(&response).map(|_| Some(response.bytes().to_vec())),
+        }
+    }
+
+    async fn put_object(&self, key: &str, bytes: &[u8]) -> Result<()> {
+        let response = self.bucket.put_object(key, bytes).await.map_err(s3_error)?;
+        check_status(&response)
+    }
+}
+
+#[async_trait]
+impl ImageStore for S3Store {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::S3
+    }
+
+    async fn put(&self, bucket: &str, bytes: Vec<u8>) -> Result<StoredImage> {
+        image::guess_format(&bytes).map_err(|e| Error::StoreError(e.to_string()))?;
+
+        let image_id = Uuid::new_v4().to_string();
+        let prefix = self.image_prefix(bucket, &image_id)?;
+        self.put_object(&format!("{}{}", prefix, ORIGINAL), &bytes)
+            .await?;
+
+        debug!("{:<12} - stored {} in {}", "S3_STORE", image_id, bucket);
+        Ok(StoredImage {
+            image_id,
+            checksum: None,
+            hash: Some(object_hash(&bytes)),
+        })
+    }
+
+    /// Goes up as a multipart upload, one part at a time.
+    async fn put_file(&self, bucket: &str, path: &Path, hash: &str) -> Result<StoredImage> {
+        let image_id = Uuid::new_v4().to_string();
+        let prefix = self.image_prefix(bucket, &image_id)?;
+        let mut file = fs::File::open(path)
+            .await
+            .map_err(|e| Error::StoreError(e.to_string()))?;
+        let status = self
+            .bucket
+            .put_object_stream(&mut file, format!("{}{}", prefix, ORIGINAL))
+            .await
+            .map_err(s3_error)?;
+        if !(200..=299).contains(&status) {
+            return Err(Error::StoreError(format!("S3 responded with {}", status)));
+        }
+
+        debug!("{:<12} - stored {} in {}", "S3_STORE", image_id, bucket);
+        Ok(StoredImage {
+            image_id,
+            checksum: None,
+            hash: Some(hash.to_string()),
+        })
+    }
+
+    async fn get(&self, bucket: &str, image_id: &str, options: GetOptions) -> Result<StoredFile> {
+        let prefix = self.image_prefix(bucket, image_id)?;
+        let not_found = || Error::NotFound(bucket.to_string(), image_id.to_string());
+
+        let rendition = match Rendition::from_options(options)? {
+            Some(rendition) => rendition,
+            None => {
+                let original = self
+                    .get_object(&format!("{}{}", prefix, ORIGINAL))
+                    .await?
+                    .ok_or_else(not_found)?;
+                return Ok(original_file(original));
+            }
+        };
+
+        let variant = format!("{}{}", prefix, rendition.name());
+        if self.store_renditions {
+            if let Some(bytes) = self.get_object(&variant).await? {
+                return Ok(rendition.into_file(bytes));
+            }
+        }
+
+        let original = self
+            .get_object(&format!("{}{}", prefix, ORIGINAL))
+            .await?
+            .ok_or_else(not_found)?;
+        let bytes = rendition.render(original).await?;
+        if self.store_renditions {
+            self.put_object(&variant, &bytes).await?;
+        }
+        Ok(rendition.into_file(bytes))
+    }
+
+    async fn delete(&self, bucket: &str, image_id: &str) -> Result<()> {
+        let prefix = self.image_prefix(bucket, image_id)?;
+        let keys = self
+            .bucket
+            .list(prefix, None)
+            .await
+            .map_err(s3_error)?
+            .into_iter()
+            .flat_map(|page| page.contents)
+            .map(|object| object.key)
+            .collect::<Vec<String>>();
+        if keys.is_empty() {
+            return Err(Error::NotFound(bucket.to_string(), image_id.to_string()));
+        }
+
+        for key in keys {
+            let response = self.bucket.delete_object(&key).await.map_err(s3_error)?;
+            check_status(&response)?;
+        }
+        Ok(())
+    }
+
+    async fn exists(&self, bucket: &str, image_id: &str) -> Result<bool> {
+        let prefix = self.image_prefix(bucket, image_id)?;
+        let (_, status) = self
+            .bucket
+            .head_object(format!("{}{}", prefix, ORIGINAL))
+            .await
+            .map_err(s3_error)?;
+        match status {
+            200..=299 => Ok(true),
+            404 => Ok(false),
+            status => Err(Error::StoreError(format!("S3 responded with {}", status))),
+        }
+    }
+
+    async fn list(&self, bucket: &str) -> Result<Vec<String>> {
+        let prefix = format!("{}/", bucket);
+        let pages = self
+            .bucket
+            .list(prefix.clone(), Some("/".to_string()))
+            .await
+            .map_err(s3_error)?;
+
+        let mut images = pages
+            .into_iter()
+            .flat_map(|page| page.common_prefixes.unwrap_or_default())
+            .filter_map(|common| {
+                let image_id = common.prefix.strip_prefix(&prefix)?.trim_end_matches('/');
+                Uuid::parse_str(image_id).ok().map(|_| image_id.to_string())
+            })
+            .collect::<Vec<String>>();
+        images.sort();
+        Ok(images)
+    }
+}
+
+fn check_status(response: &ResponseData) -> Result<()> {
+    match response.status_code() {
+        200..=299 => Ok(()),
+        status => Err(Error::StoreError(format!(
+            "S3 responded with {}: {}",
+            status,
+            String::from_utf8_lossy(response.bytes())
+        ))),
+    }
+}
+
+fn s3_error(e: S3Error) -> Error {
+    Error::StoreError(e.to_string())
+}
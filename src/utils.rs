@@ -1,21 +1,20 @@
 use tokio::fs;
 use tracing::error;
 
-use crate::config;
-
 #[derive(Debug)]
 pub enum Error {
     FailedToReadFile,
 }
 
-pub async fn read_file(file: &str) -> Result<Vec<u8>, Error> {
-    fs::read(&format!("{}/{}", &config::config().IMAGES_DIR, &file))
+/// Reads a file given relative to `root`, usually the mount point of a storage volume.
+pub async fn read_file(root: &str, file: &str) -> Result<Vec<u8>, Error> {
+    fs::read(&format!("{}/{}", root, &file))
         .await
         .map_err(|_| Error::FailedToReadFile)
 }
 
-pub async fn delete_file(file: &str) -> Result<(), Error> {
-    fs::remove_file(&format!("{}/{}", &config::config().IMAGES_DIR, &file))
+pub async fn delete_file(root: &str, file: &str) -> Result<(), Error> {
+    fs::remove_file(&format!("{}/{}", root, &file))
         .await
         .map_err(|e| {
             error!("{:<12} - failed to delete file: {}", "FILE", e);